@@ -0,0 +1,61 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::TimerQueue;
+
+/// A [`TimerQueue`] backed by a `BTreeMap`.
+///
+/// Adding, removing and expiring timers costs O(log n).
+/// It was the storage of [`Timer`](crate::Timer) before 0.2.0.
+pub struct TimerBTree<T = Duration> {
+    map: BTreeMap<(T, u64), usize>,
+    /// The position of each key in `map`.
+    pos: Vec<Option<(T, u64)>>,
+    /// The sequence number of the next pushed or updated key.
+    seq: u64,
+}
+
+impl<T> Default for TimerBTree<T> {
+    fn default() -> Self {
+        TimerBTree {
+            map: BTreeMap::new(),
+            pos: Vec::new(),
+            seq: 0,
+        }
+    }
+}
+
+impl<T: Ord + Copy> TimerQueue<T> for TimerBTree<T> {
+    fn push(&mut self, key: usize, deadline: T) {
+        if key >= self.pos.len() {
+            self.pos.resize(key + 1, None);
+        }
+        debug_assert!(self.pos[key].is_none());
+        self.seq += 1;
+        self.map.insert((deadline, self.seq), key);
+        self.pos[key] = Some((deadline, self.seq));
+    }
+
+    fn peek(&self) -> Option<(T, usize)> {
+        self.map
+            .iter()
+            .next()
+            .map(|(&(deadline, _), &key)| (deadline, key))
+    }
+
+    fn pop(&mut self, now: T) -> Option<(T, usize)> {
+        let (deadline, key) = self.peek()?;
+        if deadline > now {
+            return None;
+        }
+        self.remove(key);
+        Some((deadline, key))
+    }
+
+    fn remove(&mut self, key: usize) -> Option<T> {
+        let (deadline, seq) = self.pos.get_mut(key)?.take()?;
+        self.map.remove(&(deadline, seq));
+        Some(deadline)
+    }
+}
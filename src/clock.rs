@@ -0,0 +1,126 @@
+use core::time::Duration;
+
+#[cfg(feature = "alloc")]
+use crate::{Deadline, Timer, TimerHandle, TimerHeap, TimerQueue};
+
+/// A source of the current time.
+///
+/// It is implemented for closures returning the time.
+pub trait Clock<T = Duration> {
+    /// Get the current time.
+    fn now(&self) -> T;
+}
+
+impl<T, F: Fn() -> T> Clock<T> for F {
+    fn now(&self) -> T {
+        self()
+    }
+}
+
+/// A timer which reads the current time from a [`Clock`].
+///
+/// # Example
+/// ```
+/// use alloc::sync::Arc;
+/// use core::sync::atomic::{AtomicU64, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::ClockedTimer;
+/// extern crate alloc;
+///
+/// let millis = Arc::new(AtomicU64::new(0));
+/// let mut timer = ClockedTimer::new({
+///     let millis = millis.clone();
+///     move || Duration::from_millis(millis.load(Ordering::SeqCst))
+/// });
+///
+/// millis.store(500, Ordering::SeqCst);
+/// timer.add_after(Duration::from_secs(1), |_| {});
+/// assert_eq!(timer.time_until_next(), Some(Duration::from_secs(1)));
+///
+/// millis.store(1200, Ordering::SeqCst);
+/// assert_eq!(timer.time_until_next(), Some(Duration::from_millis(300)));
+///
+/// millis.store(1500, Ordering::SeqCst);
+/// timer.expire_now();
+/// assert_eq!(timer.time_until_next(), None);
+/// ```
+#[cfg(feature = "alloc")]
+pub struct ClockedTimer<C, T = Duration, Q = TimerHeap<T>> {
+    timer: Timer<T, Q>,
+    clock: C,
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Clock<T>, T: Ord + Copy> ClockedTimer<C, T> {
+    /// Create a timer reading the current time from `clock`.
+    pub fn new(clock: C) -> Self {
+        ClockedTimer::with_queue(clock, TimerHeap::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Clock<T>, T: Ord + Copy, Q: TimerQueue<T>> ClockedTimer<C, T, Q> {
+    /// Create a timer reading the current time from `clock`,
+    /// which stores pending timers in `queue`.
+    pub fn with_queue(clock: C, queue: Q) -> Self {
+        ClockedTimer {
+            timer: Timer::with_queue(queue),
+            clock,
+        }
+    }
+
+    /// Add a timer at `deadline`. See [`Timer::add`].
+    pub fn add(
+        &mut self,
+        deadline: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.timer.add(deadline, callback)
+    }
+
+    /// Cancel a timer. See [`Timer::cancel`].
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.timer.cancel(handle)
+    }
+
+    /// Expire timers at the current time of the clock.
+    pub fn expire_now(&mut self) {
+        let now = self.clock.now();
+        self.timer.expire(now);
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.timer.next()
+    }
+
+    /// Get the clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Get the underlying timer.
+    pub fn timer(&mut self) -> &mut Timer<T, Q> {
+        &mut self.timer
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Clock<T>, T: Deadline, Q: TimerQueue<T>> ClockedTimer<C, T, Q> {
+    /// Add a timer which expires `delay` after the current time.
+    pub fn add_after(
+        &mut self,
+        delay: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        let deadline = self.clock.now() + delay;
+        self.timer.add(deadline, callback)
+    }
+
+    /// Get the time from now until the next timer, or zero if it is already due.
+    pub fn time_until_next(&self) -> Option<T> {
+        let next = self.timer.next()?;
+        let now = self.clock.now();
+        Some(if next > now { next - now } else { T::ZERO })
+    }
+}
@@ -0,0 +1,73 @@
+use core::time::Duration;
+
+use crate::timer::Action;
+#[cfg(doc)]
+use crate::Timer;
+use crate::{Deadline, Schedule, TimerHandle};
+
+/// The context of a callback added by [`Timer::add_with_context`].
+///
+/// It gives access to the timer while it is expiring,
+/// so that the callback can add or cancel other timers.
+pub struct TimerContext<'a, T = Duration> {
+    pub(crate) timer: &'a mut dyn TimerOps<T>,
+    pub(crate) now: T,
+}
+
+/// Operations of a [`Timer`] which do not depend on its queue type.
+pub(crate) trait TimerOps<T> {
+    /// Allocate a slot for `action` and schedule it at `deadline`.
+    fn insert(&mut self, deadline: T, action: Action<T>) -> TimerHandle;
+    fn cancel(&mut self, handle: TimerHandle) -> bool;
+    fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool;
+}
+
+impl<T: Copy> TimerContext<'_, T> {
+    /// The current time passed to [`Timer::expire`].
+    pub fn now(&self) -> T {
+        self.now
+    }
+
+    /// Add a timer. See [`Timer::add`].
+    pub fn add(
+        &mut self,
+        deadline: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.timer.insert(deadline, Action::once(callback))
+    }
+
+    /// Add a timer with context. See [`Timer::add_with_context`].
+    pub fn add_with_context(
+        &mut self,
+        deadline: T,
+        callback: impl FnOnce(&mut TimerContext<T>) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.timer
+            .insert(deadline, Action::once_with_context(callback))
+    }
+
+    /// Cancel a timer. See [`Timer::cancel`].
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.timer.cancel(handle)
+    }
+
+    /// Reschedule a timer. See [`Timer::reschedule`].
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        self.timer.reschedule(handle, deadline)
+    }
+}
+
+impl<T: Deadline + Send + Sync + 'static> TimerContext<'_, T> {
+    /// Add a periodic timer. See [`Timer::add_periodic`].
+    pub fn add_periodic(
+        &mut self,
+        first_deadline: T,
+        interval: T,
+        schedule: Schedule,
+        callback: impl FnMut(T, u64) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        let action = Action::periodic(interval, schedule, callback);
+        self.timer.insert(first_deadline, action)
+    }
+}
@@ -0,0 +1,41 @@
+use core::ops::{Add, Sub};
+use core::time::Duration;
+
+/// A deadline type with arithmetic, such as `Duration` or `u64` ticks.
+///
+/// Deadlines of any `Ord + Copy` type can be added to a timer.
+/// This trait is required by periodic timers and the timing wheel,
+/// which also use it as the type of intervals.
+pub trait Deadline: Ord + Copy + Add<Output = Self> + Sub<Output = Self> {
+    /// The zero interval.
+    const ZERO: Self;
+
+    /// Divide `self` by `interval`, returning the quotient and the remainder.
+    ///
+    /// The quotient saturates at `u64::MAX`.
+    fn div_rem(self, interval: Self) -> (u64, Self);
+}
+
+impl Deadline for Duration {
+    const ZERO: Self = Duration::ZERO;
+
+    fn div_rem(self, interval: Self) -> (u64, Self) {
+        let (nanos, interval) = (self.as_nanos(), interval.as_nanos());
+        let quotient = (nanos / interval).min(u64::MAX as u128) as u64;
+        (quotient, Duration::from_nanos((nanos % interval) as u64))
+    }
+}
+
+macro_rules! impl_deadline {
+    ($($t:ty),*) => {$(
+        impl Deadline for $t {
+            const ZERO: Self = 0;
+
+            fn div_rem(self, interval: Self) -> (u64, Self) {
+                ((self / interval) as u64, self % interval)
+            }
+        }
+    )*};
+}
+
+impl_deadline!(u32, u64, usize);
@@ -0,0 +1,163 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::TimerQueue;
+
+/// Position of a key which is not in the heap.
+const NONE: usize = usize::MAX;
+
+/// A [`TimerQueue`] backed by a binary min-heap.
+///
+/// Unlike `alloc::collections::BinaryHeap`, every entry is indexed by its key,
+/// so that a timer can be removed from the middle of the heap.
+/// Adding, removing and expiring timers costs O(log n).
+///
+/// This is the default queue of [`Timer`](crate::Timer).
+pub struct TimerHeap<T = Duration> {
+    nodes: Vec<Node<T>>,
+    /// The position of each key in `nodes`.
+    pos: Vec<usize>,
+    /// The sequence number of the next pushed or updated key.
+    seq: u64,
+}
+
+struct Node<T> {
+    deadline: T,
+    seq: u64,
+    key: usize,
+}
+
+impl<T: Ord + Copy> Node<T> {
+    fn less(&self, other: &Node<T>) -> bool {
+        (self.deadline, self.seq) < (other.deadline, other.seq)
+    }
+}
+
+impl<T> TimerHeap<T> {
+    /// Create an empty heap.
+    pub const fn new() -> Self {
+        TimerHeap {
+            nodes: Vec::new(),
+            pos: Vec::new(),
+            seq: 0,
+        }
+    }
+}
+
+impl<T> Default for TimerHeap<T> {
+    fn default() -> Self {
+        TimerHeap::new()
+    }
+}
+
+impl<T: Ord + Copy> TimerQueue<T> for TimerHeap<T> {
+    fn push(&mut self, key: usize, deadline: T) {
+        let seq = self.next_seq();
+        self.insert(key, deadline, seq);
+    }
+
+    fn peek(&self) -> Option<(T, usize)> {
+        self.nodes.first().map(|n| (n.deadline, n.key))
+    }
+
+    fn pop(&mut self, now: T) -> Option<(T, usize)> {
+        match self.nodes.first() {
+            Some(n) if n.deadline <= now => Some(self.remove_at(0)),
+            _ => None,
+        }
+    }
+
+    fn remove(&mut self, key: usize) -> Option<T> {
+        match self.pos.get(key) {
+            Some(&i) if i != NONE => Some(self.remove_at(i).0),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, key: usize, deadline: T) -> bool {
+        match self.pos.get(key) {
+            Some(&i) if i != NONE => {
+                self.nodes[i].deadline = deadline;
+                self.nodes[i].seq = self.next_seq();
+                self.sift_down(i);
+                self.sift_up(i);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: Ord + Copy> TimerHeap<T> {
+    /// Returns whether the heap is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Insert a `key` with its `deadline` and sequence number `seq`.
+    ///
+    /// The `key` must not be in the heap.
+    pub(crate) fn insert(&mut self, key: usize, deadline: T, seq: u64) {
+        if key >= self.pos.len() {
+            self.pos.resize(key + 1, NONE);
+        }
+        debug_assert_eq!(self.pos[key], NONE);
+        let i = self.nodes.len();
+        self.nodes.push(Node { deadline, seq, key });
+        self.pos[key] = i;
+        self.sift_up(i);
+    }
+
+    fn remove_at(&mut self, i: usize) -> (T, usize) {
+        let last = self.nodes.len() - 1;
+        self.swap(i, last);
+        let node = self.nodes.pop().unwrap();
+        self.pos[node.key] = NONE;
+        if i < last {
+            self.sift_down(i);
+            self.sift_up(i);
+        }
+        (node.deadline, node.key)
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.nodes[i].less(&self.nodes[parent]) {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let left = 2 * i + 1;
+            let right = left + 1;
+            let mut min = i;
+            if left < self.nodes.len() && self.nodes[left].less(&self.nodes[min]) {
+                min = left;
+            }
+            if right < self.nodes.len() && self.nodes[right].less(&self.nodes[min]) {
+                min = right;
+            }
+            if min == i {
+                break;
+            }
+            self.swap(i, min);
+            i = min;
+        }
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.seq += 1;
+        self.seq
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.nodes.swap(i, j);
+        self.pos[self.nodes[i].key] = i;
+        self.pos[self.nodes[j].key] = j;
+    }
+}
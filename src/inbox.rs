@@ -0,0 +1,196 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use core::time::Duration;
+
+use crate::context::TimerOps;
+use crate::timer::Action;
+use crate::{Timer, TimerHeap, TimerQueue};
+
+/// A lock-free inbox of timers added from any CPU to an [`InboxTimer`].
+///
+/// Adding a timer never waits, even while the owning CPU is expiring its timer.
+/// The timers are moved into the owning timer in the order they were added.
+pub struct TimerInbox<T = Duration> {
+    /// A stack of timers, the last added first.
+    head: AtomicPtr<Node<T>>,
+    /// Timers are sent to the owning CPU.
+    _marker: PhantomData<*mut Node<T>>,
+}
+
+struct Node<T> {
+    deadline: T,
+    action: Action<T>,
+    next: *mut Node<T>,
+}
+
+// SAFETY: nodes are only accessed by the thread which pushes or takes them.
+unsafe impl<T: Send> Send for TimerInbox<T> {}
+unsafe impl<T: Send> Sync for TimerInbox<T> {}
+
+impl<T> TimerInbox<T> {
+    /// Create an empty inbox.
+    pub const fn new() -> Self {
+        TimerInbox {
+            head: AtomicPtr::new(ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Take all timers, the last added first.
+    fn take(&self) -> *mut Node<T> {
+        self.head.swap(ptr::null_mut(), Ordering::Acquire)
+    }
+}
+
+impl<T: Copy> TimerInbox<T> {
+    /// Add a timer. See [`Timer::add`].
+    ///
+    /// The timer can not be cancelled, since it has no handle until it is moved into the timer.
+    pub fn add(&self, deadline: T, callback: impl FnOnce(T) + Send + Sync + 'static) {
+        self.push(deadline, Action::once(callback));
+    }
+
+    fn push(&self, deadline: T, action: Action<T>) {
+        let node = Box::into_raw(Box::new(Node {
+            deadline,
+            action,
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the node is not shared until it is pushed.
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+impl<T> Default for TimerInbox<T> {
+    fn default() -> Self {
+        TimerInbox::new()
+    }
+}
+
+impl<T> Drop for TimerInbox<T> {
+    fn drop(&mut self) {
+        let mut node = self.take();
+        while !node.is_null() {
+            // SAFETY: the nodes are taken from the inbox and owned by us.
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next;
+        }
+    }
+}
+
+/// A timer owned by a CPU, which other CPUs add timers to through a [`TimerInbox`].
+///
+/// The inbox is drained into the timer at the start of
+/// [`expire`](InboxTimer::expire) and [`next`](InboxTimer::next).
+///
+/// # Example
+/// ```
+/// use alloc::sync::Arc;
+/// use core::sync::atomic::{AtomicU32, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::{InboxTimer, TimerInbox};
+/// extern crate alloc;
+///
+/// static INBOX: TimerInbox = TimerInbox::new();
+/// let mut timer = InboxTimer::new(&INBOX);
+/// let count = Arc::new(AtomicU32::new(0));
+///
+/// // add timers from other CPUs
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| {
+///         let count = count.clone();
+///         std::thread::spawn(move || {
+///             INBOX.add(Duration::from_secs(1), move |_| {
+///                 count.fetch_add(1, Ordering::SeqCst);
+///             })
+///         })
+///     })
+///     .collect();
+/// for thread in threads {
+///     thread.join().unwrap();
+/// }
+///
+/// assert_eq!(timer.next(), Some(Duration::from_secs(1)));
+/// timer.expire(Duration::from_secs(1));
+/// assert_eq!(count.load(Ordering::SeqCst), 4);
+/// ```
+pub struct InboxTimer<'a, T = Duration, Q = TimerHeap<T>> {
+    timer: Timer<T, Q>,
+    inbox: &'a TimerInbox<T>,
+}
+
+impl<'a, T: Ord + Copy> InboxTimer<'a, T> {
+    /// Create a timer which receives timers from `inbox`.
+    pub fn new(inbox: &'a TimerInbox<T>) -> Self {
+        InboxTimer::with_queue(inbox, TimerHeap::new())
+    }
+}
+
+impl<'a, T: Ord + Copy, Q: TimerQueue<T>> InboxTimer<'a, T, Q> {
+    /// Create a timer which receives timers from `inbox`,
+    /// and stores pending timers in `queue`.
+    pub fn with_queue(inbox: &'a TimerInbox<T>, queue: Q) -> Self {
+        InboxTimer {
+            timer: Timer::with_queue(queue),
+            inbox,
+        }
+    }
+
+    /// Get the inbox.
+    pub fn inbox(&self) -> &'a TimerInbox<T> {
+        self.inbox
+    }
+
+    /// Move timers from the inbox into the timer.
+    pub fn drain(&mut self) {
+        // reverse the stack, so that timers are moved in the order they were added
+        let mut node = self.inbox.take();
+        let mut reversed = ptr::null_mut();
+        while !node.is_null() {
+            // SAFETY: the nodes are taken from the inbox and owned by us.
+            let next = unsafe { (*node).next };
+            unsafe { (*node).next = reversed };
+            reversed = node;
+            node = next;
+        }
+        while !reversed.is_null() {
+            // SAFETY: as above.
+            let boxed = unsafe { Box::from_raw(reversed) };
+            reversed = boxed.next;
+            self.timer.insert(boxed.deadline, boxed.action);
+        }
+    }
+
+    /// Expire timers, after draining the inbox. See [`Timer::expire`].
+    pub fn expire(&mut self, now: T) {
+        self.drain();
+        self.timer.expire(now);
+    }
+
+    /// Get next timer, after draining the inbox.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<T> {
+        self.drain();
+        self.timer.next()
+    }
+
+    /// Get the underlying timer, after draining the inbox.
+    ///
+    /// Timers can be added to it locally, with handles to cancel them.
+    pub fn timer(&mut self) -> &mut Timer<T, Q> {
+        self.drain();
+        &mut self.timer
+    }
+}
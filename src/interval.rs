@@ -0,0 +1,111 @@
+#[cfg(feature = "stream")]
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+#[cfg(doc)]
+use crate::Schedule;
+use crate::{SharedTimer, TimerHandle};
+
+/// A stream of ticks returned by [`SharedTimer::interval`].
+///
+/// It is backed by a periodic timer, so ticks do not drift.
+/// When the timer is expired late, the missed ticks are handled according to its [`Schedule`]:
+/// with [`Schedule::FixedRate`] every missed tick is yielded in a burst,
+/// otherwise a single tick is yielded.
+///
+/// With the `stream` feature, it implements `futures_core::Stream`.
+///
+/// # Example
+/// ```
+/// use std::task::{Context, Poll, Waker};
+/// use core::cell::RefCell;
+/// use core::time::Duration;
+/// use naive_timer::{Schedule, SharedTimer, Timer};
+///
+/// let timer = RefCell::new(Timer::default());
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// let period = Duration::from_secs(1);
+/// let mut interval = timer.interval(period, period, Schedule::FixedRate);
+/// assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
+///
+/// // the ticks at 1s and 2s are missed
+/// timer.borrow_mut().expire(Duration::from_millis(2500));
+/// assert_eq!(interval.poll_tick(&mut cx), Poll::Ready(()));
+/// assert_eq!(interval.poll_tick(&mut cx), Poll::Ready(()));
+/// assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
+///
+/// // a dropped interval leaves nothing behind
+/// drop(interval);
+/// assert_eq!(timer.borrow().next(), None);
+/// ```
+///
+/// Missed ticks are counted at once, however late the timer is expired:
+/// ```
+/// use std::task::{Context, Poll, Waker};
+/// use core::cell::RefCell;
+/// use naive_timer::{Schedule, SharedTimer, Timer};
+///
+/// let timer = RefCell::new(Timer::<u64>::default());
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// let mut interval = timer.interval(1, 1, Schedule::FixedRate);
+/// timer.borrow_mut().expire(1_000_000_000);
+/// assert_eq!(timer.borrow().next(), Some(1_000_000_001));
+/// assert_eq!(interval.poll_tick(&mut cx), Poll::Ready(()));
+/// ```
+pub struct Interval<'a, S: SharedTimer<T>, T: Ord + Copy = Duration> {
+    timer: &'a S,
+    handle: TimerHandle,
+    period: T,
+}
+
+impl<'a, S: SharedTimer<T>, T: Ord + Copy> Interval<'a, S, T> {
+    pub(crate) fn new(timer: &'a S, handle: TimerHandle, period: T) -> Self {
+        Interval {
+            timer,
+            handle,
+            period,
+        }
+    }
+
+    /// The period of ticks.
+    pub fn period(&self) -> T {
+        self.period
+    }
+
+    /// Poll for the next tick.
+    ///
+    /// Returns `Poll::Pending` and wakes the task on the next tick if there are no ticks yet.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let handle = self.handle;
+        if self
+            .timer
+            .with(|timer| timer.poll_ticker(handle, cx.waker()))
+        {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// The period is never pinned.
+impl<S: SharedTimer<T>, T: Ord + Copy> Unpin for Interval<'_, S, T> {}
+
+#[cfg(feature = "stream")]
+impl<S: SharedTimer<T>, T: Ord + Copy> futures_core::Stream for Interval<'_, S, T> {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+impl<S: SharedTimer<T>, T: Ord + Copy> Drop for Interval<'_, S, T> {
+    fn drop(&mut self) {
+        let handle = self.handle;
+        self.timer.with(|timer| timer.cancel(handle));
+    }
+}
@@ -65,90 +65,105 @@
 //! handle.join().unwrap();
 //! ```
 //!
-//! # Limitations
+//! # Example: cancellation
+//! ```
+//! use core::time::Duration;
+//! use naive_timer::Timer;
 //!
-//! For simplicity, **timer cancellation** is not supported.
+//! let mut timer = Timer::default();
+//! let handle = timer.add(Duration::from_secs(1), |_| unreachable!());
+//! assert_eq!(timer.next(), Some(Duration::from_secs(1)));
 //!
-//! The callback function should check the current time `now` and its own information,
-//! to decide whether it is still a valid event.
+//! // the event is removed from the timer immediately
+//! assert!(timer.cancel(handle));
+//! assert_eq!(timer.next(), None);
+//!
+//! // a handle is no longer valid once its timer is cancelled or fired
+//! assert!(!timer.cancel(handle));
+//! ```
 
 #![no_std]
 #![deny(missing_docs)]
 #![deny(warnings)]
 
-use alloc::boxed::Box;
-use alloc::collections::BinaryHeap;
-use core::cmp::Ordering;
-use core::time::Duration;
+#[cfg(feature = "alloc")]
+pub use self::btree::TimerBTree;
+pub use self::clock::Clock;
+#[cfg(feature = "alloc")]
+pub use self::clock::ClockedTimer;
+#[cfg(feature = "alloc")]
+pub use self::context::TimerContext;
+pub use self::deadline::Deadline;
+#[cfg(feature = "alloc")]
+pub use self::heap::TimerHeap;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use self::inbox::{InboxTimer, TimerInbox};
+#[cfg(feature = "alloc")]
+pub use self::interval::Interval;
+#[cfg(feature = "alloc")]
+pub use self::local::LocalTimer;
+#[cfg(feature = "alloc")]
+pub use self::payload::PayloadTimer;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use self::percpu::PerCpuTimer;
+#[cfg(feature = "alloc")]
+pub use self::queue::TimerQueue;
+pub use self::simulator::SimulatedClock;
+#[cfg(feature = "alloc")]
+pub use self::simulator::{Simulator, StepLimitExceeded};
+#[cfg(feature = "alloc")]
+pub use self::sleep::{Elapsed, SharedTimer, Sleep, Timeout};
+pub use self::static_timer::{StaticTimer, TimerFull};
+#[cfg(feature = "sync")]
+pub use self::sync::SyncTimer;
+#[cfg(feature = "alloc")]
+pub use self::timer::{Callback, ExpireResult, ExpiredTimer, Schedule, Timer, TimerCallback};
+#[cfg(feature = "alloc")]
+pub use self::wheel::TimingWheel;
+pub use self::wrapping::TickExtender;
+#[cfg(feature = "alloc")]
+pub use self::wrapping::WrappingTimer;
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
-/// A naive timer.
-#[derive(Default)]
-pub struct Timer {
-    events: BinaryHeap<Event>,
-}
-
-/// The type of callback function.
-type Callback = Box<dyn FnOnce(Duration) + Send + Sync + 'static>;
-
-impl Timer {
-    /// Add a timer.
-    ///
-    /// The `callback` will be called on timer expired after `deadline`.
-    pub fn add(
-        &mut self,
-        deadline: Duration,
-        callback: impl FnOnce(Duration) + Send + Sync + 'static,
-    ) {
-        let event = Event {
-            deadline,
-            callback: Box::new(callback),
-        };
-        self.events.push(event);
-    }
-
-    /// Expire timers.
-    ///
-    /// Given the current time `now`, trigger and remove all expired timers.
-    pub fn expire(&mut self, now: Duration) {
-        while let Some(t) = self.events.peek() {
-            if t.deadline > now {
-                break;
-            }
-            let event = self.events.pop().unwrap();
-            (event.callback)(now);
-        }
-    }
-
-    /// Get next timer.
-    pub fn next(&self) -> Option<Duration> {
-        self.events.peek().map(|e| e.deadline)
-    }
-}
-
-struct Event {
-    deadline: Duration,
-    callback: Callback,
-}
-
-impl PartialEq for Event {
-    fn eq(&self, other: &Self) -> bool {
-        self.deadline.eq(&other.deadline)
-    }
-}
-
-impl Eq for Event {}
-
-// BinaryHeap is a max-heap. So we need to reverse the order.
-impl PartialOrd for Event {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.deadline.partial_cmp(&self.deadline)
-    }
-}
+#[cfg(feature = "alloc")]
+mod btree;
+mod clock;
+#[cfg(feature = "alloc")]
+mod context;
+mod deadline;
+#[cfg(feature = "alloc")]
+mod heap;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod inbox;
+#[cfg(feature = "alloc")]
+mod interval;
+#[cfg(feature = "alloc")]
+mod local;
+#[cfg(feature = "alloc")]
+mod payload;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod percpu;
+#[cfg(feature = "alloc")]
+mod queue;
+mod simulator;
+#[cfg(feature = "alloc")]
+mod sleep;
+mod static_timer;
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "alloc")]
+mod timer;
+#[cfg(feature = "alloc")]
+mod wheel;
+mod wrapping;
 
-impl Ord for Event {
-    fn cmp(&self, other: &Event) -> Ordering {
-        other.deadline.cmp(&self.deadline)
-    }
+/// A handle to a timer, returned when the timer is added.
+///
+/// A handle becomes stale once its timer is fired or cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    pub(crate) index: usize,
+    pub(crate) generation: u32,
 }
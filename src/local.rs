@@ -0,0 +1,110 @@
+use alloc::boxed::Box;
+use core::time::Duration;
+
+use crate::{
+    Clock, Deadline, ExpireResult, Timer, TimerCallback, TimerHandle, TimerHeap, TimerQueue,
+};
+
+/// The type of callbacks of [`LocalTimer`].
+type LocalCallback<T> = Box<dyn FnOnce(T) + 'static>;
+
+impl<T> TimerCallback<T> for LocalCallback<T> {
+    fn call(self, now: T) {
+        self(now)
+    }
+}
+
+/// A timer for a single thread, whose callbacks need not be `Send` or `Sync`.
+///
+/// Callbacks can capture `Rc`, `RefCell` and other single-thread state.
+/// The timer itself is not `Send`, so it never leaves the thread.
+/// Timers are ordered and fired as [`Timer`] does.
+///
+/// # Example
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use core::time::Duration;
+/// use naive_timer::LocalTimer;
+///
+/// let mut timer = LocalTimer::default();
+/// let order = Rc::new(RefCell::new(Vec::new()));
+///
+/// for i in [2, 1, 3] {
+///     let order = order.clone();
+///     timer.add(Duration::from_secs(i), move |_| order.borrow_mut().push(i));
+/// }
+///
+/// timer.expire(Duration::from_secs(2));
+/// assert_eq!(*order.borrow(), [1, 2]);
+/// assert_eq!(timer.next(), Some(Duration::from_secs(3)));
+/// ```
+///
+/// The timer can not be sent to another thread:
+/// ```compile_fail
+/// use naive_timer::LocalTimer;
+///
+/// let timer = LocalTimer::<u64>::default();
+/// std::thread::spawn(move || timer.next());
+/// ```
+pub struct LocalTimer<T = Duration, Q = TimerHeap<T>> {
+    timer: Timer<T, Q, LocalCallback<T>>,
+}
+
+impl<T: Ord + Copy> Default for LocalTimer<T> {
+    fn default() -> Self {
+        LocalTimer::with_queue(TimerHeap::new())
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>> LocalTimer<T, Q> {
+    /// Create a timer which stores pending timers in `queue`.
+    pub const fn with_queue(queue: Q) -> Self {
+        LocalTimer {
+            timer: Timer::with_callback_queue(queue),
+        }
+    }
+
+    /// Add a timer. See [`Timer::add`].
+    pub fn add(&mut self, deadline: T, callback: impl FnOnce(T) + 'static) -> TimerHandle {
+        self.timer.add_callback(deadline, Box::new(callback))
+    }
+
+    /// Cancel a timer. See [`Timer::cancel`].
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.timer.cancel(handle)
+    }
+
+    /// Reschedule a timer. See [`Timer::reschedule`].
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        self.timer.reschedule(handle, deadline)
+    }
+
+    /// Expire timers. See [`Timer::expire`].
+    pub fn expire(&mut self, now: T) {
+        self.timer.expire(now);
+    }
+
+    /// Expire timers, firing at most `max_events` of them. See [`Timer::expire_bounded`].
+    pub fn expire_bounded(&mut self, now: T, max_events: usize) -> ExpireResult {
+        self.timer.expire_bounded(now, max_events)
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.timer.next()
+    }
+}
+
+impl<T: Deadline, Q: TimerQueue<T>> LocalTimer<T, Q> {
+    /// Expire timers, until `budget` has passed on `clock` since the call.
+    /// See [`Timer::expire_with_budget`].
+    pub fn expire_with_budget<C: Clock<T>>(
+        &mut self,
+        now: T,
+        clock: &C,
+        budget: T,
+    ) -> ExpireResult {
+        self.timer.expire_with_budget(now, clock, budget)
+    }
+}
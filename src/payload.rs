@@ -0,0 +1,173 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::time::Duration;
+
+use crate::{TimerHandle, TimerHeap, TimerQueue};
+
+/// A timer which returns a payload of each expired timer, instead of calling a callback.
+///
+/// Payloads are stored inline, so adding a timer does not allocate
+/// other than to grow the storage. [`Timer`](crate::Timer) is built on it.
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use naive_timer::PayloadTimer;
+///
+/// // connection IDs
+/// let mut timer = PayloadTimer::<u32>::default();
+/// timer.add(Duration::from_secs(2), 1);
+/// let handle = timer.add(Duration::from_secs(1), 2);
+/// timer.add(Duration::from_secs(1), 3);
+///
+/// assert_eq!(timer.cancel(handle), Some(2));
+/// let expired: Vec<_> = timer.expire(Duration::from_secs(2)).collect();
+/// assert_eq!(expired, [3, 1]);
+/// ```
+pub struct PayloadTimer<P, T = Duration, Q = TimerHeap<T>> {
+    events: Q,
+    slots: Vec<Slot<P>>,
+    free: Vec<usize>,
+    _deadline: PhantomData<T>,
+}
+
+/// The storage of a timer's payload.
+struct Slot<P> {
+    /// Incremented every time the slot is freed, to detect stale handles.
+    generation: u32,
+    payload: Option<P>,
+}
+
+impl<P, T: Ord + Copy> Default for PayloadTimer<P, T> {
+    fn default() -> Self {
+        PayloadTimer::with_queue(TimerHeap::new())
+    }
+}
+
+impl<P, T: Ord + Copy, Q: TimerQueue<T>> PayloadTimer<P, T, Q> {
+    /// Create a timer which stores pending timers in `queue`.
+    pub const fn with_queue(queue: Q) -> Self {
+        PayloadTimer {
+            events: queue,
+            slots: Vec::new(),
+            free: Vec::new(),
+            _deadline: PhantomData,
+        }
+    }
+
+    /// Add a timer with `payload`, which expires after `deadline`.
+    ///
+    /// Returns a handle which can be used to cancel the timer.
+    pub fn add(&mut self, deadline: T, payload: P) -> TimerHandle {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].payload = Some(payload);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    payload: Some(payload),
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.events.push(index, deadline);
+        self.handle(index)
+    }
+
+    /// Cancel a timer, returning its payload.
+    ///
+    /// Returns `None` if the timer has already expired or been cancelled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<P> {
+        self.get_mut(handle)?;
+        self.events.remove(handle.index);
+        Some(self.release(handle.index))
+    }
+
+    /// Reschedule a timer to a new `deadline`. See [`Timer::reschedule`](crate::Timer::reschedule).
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        self.get_mut(handle).is_some() && self.events.update(handle.index, deadline)
+    }
+
+    /// Get the payload of a pending timer.
+    pub fn get_mut(&mut self, handle: TimerHandle) -> Option<&mut P> {
+        match self.slots.get_mut(handle.index) {
+            Some(slot) if slot.generation == handle.generation => slot.payload.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Remove the first expired timer at `now`, returning its deadline and payload.
+    pub fn pop_expired(&mut self, now: T) -> Option<(T, P)> {
+        let (deadline, index) = self.events.pop(now)?;
+        Some((deadline, self.release(index)))
+    }
+
+    /// Remove expired timers at `now`, returning their payloads.
+    ///
+    /// Payloads are returned in the order [`Timer::expire`](crate::Timer::expire) calls callbacks.
+    /// Expired timers which are not yet returned stay in the timer when the iterator is dropped.
+    pub fn expire(&mut self, now: T) -> impl Iterator<Item = P> + '_ {
+        core::iter::from_fn(move || self.pop_expired(now).map(|(_, payload)| payload))
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.events.peek().map(|(deadline, _)| deadline)
+    }
+
+    /// Move all pending timers of `other` into `self`. See [`Timer::append`](crate::Timer::append).
+    ///
+    /// Returns the handles of the moved timers in `other` and in `self`.
+    pub fn append<Q2: TimerQueue<T>>(
+        &mut self,
+        other: &mut PayloadTimer<P, T, Q2>,
+    ) -> Vec<(TimerHandle, TimerHandle)> {
+        let mut moved = Vec::new();
+        while let Some((deadline, index)) = other.events.peek() {
+            other.events.remove(index);
+            let handle = other.handle(index);
+            let payload = other.release(index);
+            moved.push((handle, self.add(deadline, payload)));
+        }
+        moved
+    }
+
+    /// Returns `true` if any pending timer has a payload satisfying `f`.
+    pub(crate) fn any(&self, f: impl FnMut(&P) -> bool) -> bool {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.payload.as_ref())
+            .any(f)
+    }
+
+    /// Remove the first expired timer at `now` from the queue, keeping its payload.
+    ///
+    /// The timer is still pending, until it is pushed back by [`PayloadTimer::push_back`]
+    /// or cancelled.
+    pub(crate) fn pop_key(&mut self, now: T) -> Option<(T, TimerHandle)> {
+        let (deadline, index) = self.events.pop(now)?;
+        Some((deadline, self.handle(index)))
+    }
+
+    /// Push a timer removed by [`PayloadTimer::pop_key`] back to the queue.
+    pub(crate) fn push_back(&mut self, handle: TimerHandle, deadline: T) {
+        self.events.push(handle.index, deadline);
+    }
+
+    /// Free the slot at `index`, returning its payload.
+    pub(crate) fn release(&mut self, index: usize) -> P {
+        let slot = &mut self.slots[index];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        slot.payload.take().unwrap()
+    }
+
+    fn handle(&self, index: usize) -> TimerHandle {
+        TimerHandle {
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+}
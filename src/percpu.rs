@@ -0,0 +1,205 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{ExpiredTimer, SharedTimer, TimerHandle};
+
+/// A timer per CPU, each shared by a [`SharedTimer`] such as `SyncTimer`.
+///
+/// Every CPU adds and expires timers of its own,
+/// and all pending timers of a CPU can be migrated to another one when it goes offline.
+///
+/// # Example
+/// ```
+/// use alloc::sync::Arc;
+/// use std::task::{Context, Poll, Waker};
+/// use core::cell::RefCell;
+/// use core::future::Future;
+/// use core::pin::Pin;
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::{PerCpuTimer, SharedTimer, Timer};
+/// extern crate alloc;
+///
+/// let timers = PerCpuTimer::<RefCell<Timer>>::new(2);
+/// let event = Arc::new(AtomicBool::new(false));
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// timers.add_on(1, Duration::from_secs(1), {
+///     let event = event.clone();
+///     move |_| event.store(true, Ordering::SeqCst)
+/// });
+/// let handle = timers.add_on(1, Duration::from_secs(2), |_| unreachable!());
+/// let mut sleep = timers.get(1).sleep_until(Duration::from_secs(3));
+/// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+///
+/// // CPU 1 goes offline
+/// timers.migrate_all(1, 0);
+/// assert_eq!(timers.next(1), None);
+/// assert_eq!(timers.next(0), Some(Duration::from_secs(1)));
+///
+/// // handles and futures of CPU 1 are still valid
+/// assert!(timers.cancel_on(1, handle));
+/// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+///
+/// timers.expire(0, Duration::from_secs(2));
+/// assert_eq!(event.load(Ordering::SeqCst), true);
+/// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+///
+/// timers.expire(0, Duration::from_secs(3));
+/// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Ready(()));
+/// ```
+pub struct PerCpuTimer<S> {
+    cpus: Box<[S]>,
+    /// The CPU which expires the timer of each CPU.
+    ///
+    /// Timers are protected by their own locks, so it is accessed with relaxed ordering.
+    owners: Box<[AtomicUsize]>,
+}
+
+impl<S: Default> PerCpuTimer<S> {
+    /// Create a timer for each of `cpus` CPUs.
+    pub fn new(cpus: usize) -> Self {
+        PerCpuTimer::from((0..cpus).map(|_| S::default()).collect::<Vec<_>>())
+    }
+}
+
+impl<S> PerCpuTimer<S> {
+    /// The number of CPUs.
+    pub fn len(&self) -> usize {
+        self.cpus.len()
+    }
+
+    /// Returns `true` if there are no CPUs.
+    pub fn is_empty(&self) -> bool {
+        self.cpus.is_empty()
+    }
+
+    /// Get the timer of `cpu`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cpu` is out of range, as other methods do.
+    pub fn get(&self, cpu: usize) -> &S {
+        &self.cpus[cpu]
+    }
+
+    /// Add a timer on `cpu`. See [`Timer::add`](crate::Timer::add).
+    pub fn add_on<T: Ord + Copy>(
+        &self,
+        cpu: usize,
+        deadline: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle
+    where
+        S: SharedTimer<T>,
+    {
+        self.cpus[cpu].with(|timer| timer.add(deadline, callback))
+    }
+
+    /// Cancel a timer on `cpu`. See [`Timer::cancel`](crate::Timer::cancel).
+    ///
+    /// The timer is still on the CPU it is added on, even if the CPU has been migrated.
+    pub fn cancel_on<T: Ord + Copy>(&self, cpu: usize, handle: TimerHandle) -> bool
+    where
+        S: SharedTimer<T>,
+    {
+        let action = self.cpus[cpu].with(|timer| timer.take(handle));
+        action.is_some()
+    }
+
+    /// Expire timers on `cpu`, and on CPUs migrated to it. See [`Timer::expire`](crate::Timer::expire).
+    ///
+    /// Each expired timer is removed from its timer,
+    /// and its callback is called after the timer is released.
+    /// Timers of different CPUs are fired in the order of their deadlines.
+    pub fn expire<T: Ord + Copy>(&self, cpu: usize, now: T)
+    where
+        S: SharedTimer<T>,
+    {
+        loop {
+            let first = self
+                .owned(cpu)
+                .filter_map(|timer| Some((timer.with(|timer| timer.next())?, timer)))
+                .filter(|&(deadline, _)| deadline <= now)
+                .min_by_key(|&(deadline, _)| deadline);
+            let Some((_, timer)) = first else {
+                return;
+            };
+            if let Some(callback) = timer.with(|timer| timer.pop_expired(now)) {
+                ExpiredTimer::new(callback, now).call(timer);
+            }
+        }
+    }
+
+    /// Get next timer on `cpu`, and on CPUs migrated to it.
+    pub fn next<T: Ord + Copy>(&self, cpu: usize) -> Option<T>
+    where
+        S: SharedTimer<T>,
+    {
+        self.owned(cpu)
+            .filter_map(|timer| timer.with(|timer| timer.next()))
+            .min()
+    }
+
+    /// Migrate all pending timers from CPU `from` to CPU `to`, when `from` goes offline.
+    ///
+    /// The timers stay where they are, but they are expired by `to` from now on,
+    /// so their handles stay valid, and futures waiting on them are woken by `to`.
+    /// Timers added on `from` later are expired by `to` as well,
+    /// until `from` is [restored](PerCpuTimer::restore).
+    /// In exchange, [`PerCpuTimer::expire`] on `to` locks the timer of each CPU
+    /// migrated to it once more for every fired timer, to find the earliest one.
+    /// Use [`Timer::append`](crate::Timer::append) to move timers with callbacks instead.
+    ///
+    /// Timers of CPUs migrated to `from` are migrated to `to` along with them.
+    /// If `to` has been migrated itself, the timers go where those of `to` have gone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timers of `to` go to `from`, including when `from` and `to` are the same,
+    /// since nothing would expire them:
+    /// ```should_panic
+    /// use core::cell::RefCell;
+    /// use naive_timer::{PerCpuTimer, Timer};
+    ///
+    /// let timers = PerCpuTimer::<RefCell<Timer>>::new(3);
+    /// timers.migrate_all(2, 1);
+    /// timers.migrate_all(1, 2);
+    /// ```
+    pub fn migrate_all(&self, from: usize, to: usize) {
+        let to = self.owners[to].load(Ordering::Relaxed);
+        assert_ne!(from, to, "timers of CPU {from} would never be expired");
+        for owner in self.owners.iter() {
+            if owner.load(Ordering::Relaxed) == from {
+                owner.store(to, Ordering::Relaxed);
+            }
+        }
+        self.owners[from].store(to, Ordering::Relaxed);
+    }
+
+    /// Expire the timers of `cpu` on itself again, when it comes back online.
+    ///
+    /// Timers of other CPUs migrated to `cpu` are not moved back.
+    pub fn restore(&self, cpu: usize) {
+        self.owners[cpu].store(cpu, Ordering::Relaxed);
+    }
+
+    /// Get the timers expired by `cpu`.
+    fn owned(&self, cpu: usize) -> impl Iterator<Item = &S> {
+        self.cpus
+            .iter()
+            .zip(self.owners.iter())
+            .filter(move |(_, owner)| owner.load(Ordering::Relaxed) == cpu)
+            .map(|(timer, _)| timer)
+    }
+}
+
+impl<S> From<Vec<S>> for PerCpuTimer<S> {
+    fn from(cpus: Vec<S>) -> Self {
+        PerCpuTimer {
+            owners: (0..cpus.len()).map(AtomicUsize::new).collect(),
+            cpus: cpus.into_boxed_slice(),
+        }
+    }
+}
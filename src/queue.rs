@@ -0,0 +1,81 @@
+/// The storage of pending timers, ordered by deadline.
+///
+/// Every timer is identified by a `key` assigned by [`Timer`](crate::Timer).
+/// Keys are small integers which are reused after removed,
+/// so they can index a `Vec` directly.
+/// Keys with the same deadline `T` must be ordered first-in-first-out.
+///
+/// This crate provides [`TimerHeap`](crate::TimerHeap), [`TimerBTree`](crate::TimerBTree)
+/// and [`TimingWheel`](crate::TimingWheel).
+///
+/// # Example
+///
+/// A queue which keeps keys in a sorted `Vec`, for a handful of timers:
+/// ```
+/// use core::time::Duration;
+/// use naive_timer::{Timer, TimerQueue};
+///
+/// #[derive(Default)]
+/// struct SortedVec(Vec<(Duration, usize)>);
+///
+/// impl TimerQueue<Duration> for SortedVec {
+///     fn push(&mut self, key: usize, deadline: Duration) {
+///         // insert after all keys with the same deadline
+///         let i = self.0.partition_point(|&(d, _)| d <= deadline);
+///         self.0.insert(i, (deadline, key));
+///     }
+///
+///     fn peek(&self) -> Option<(Duration, usize)> {
+///         self.0.first().copied()
+///     }
+///
+///     fn pop(&mut self, now: Duration) -> Option<(Duration, usize)> {
+///         match self.0.first() {
+///             Some(&(deadline, _)) if deadline <= now => Some(self.0.remove(0)),
+///             _ => None,
+///         }
+///     }
+///
+///     fn remove(&mut self, key: usize) -> Option<Duration> {
+///         let i = self.0.iter().position(|&(_, k)| k == key)?;
+///         Some(self.0.remove(i).0)
+///     }
+/// }
+///
+/// let mut timer = Timer::with_queue(SortedVec::default());
+/// let handle = timer.add(Duration::from_secs(2), |_| {});
+/// timer.add(Duration::from_secs(1), |_| {});
+/// assert_eq!(timer.next(), Some(Duration::from_secs(1)));
+///
+/// timer.cancel(handle);
+/// timer.expire(Duration::from_secs(1));
+/// assert_eq!(timer.next(), None);
+/// ```
+pub trait TimerQueue<T> {
+    /// Push a `key` with its `deadline`.
+    ///
+    /// The `key` must not be in the queue.
+    fn push(&mut self, key: usize, deadline: T);
+
+    /// Get the key with the earliest deadline.
+    fn peek(&self) -> Option<(T, usize)>;
+
+    /// Remove and return the key with the earliest deadline,
+    /// if the deadline is not after `now`.
+    fn pop(&mut self, now: T) -> Option<(T, usize)>;
+
+    /// Remove a `key` from the queue, returning its deadline.
+    fn remove(&mut self, key: usize) -> Option<T>;
+
+    /// Change the deadline of a `key` in the queue.
+    ///
+    /// The `key` is ordered after other keys with the same deadline.
+    /// Returns `false` if the `key` is not in the queue.
+    fn update(&mut self, key: usize, deadline: T) -> bool {
+        if self.remove(key).is_none() {
+            return false;
+        }
+        self.push(key, deadline);
+        true
+    }
+}
@@ -0,0 +1,199 @@
+use core::cell::Cell;
+use core::fmt;
+use core::time::Duration;
+
+use crate::Clock;
+#[cfg(feature = "alloc")]
+use crate::{ClockedTimer, Deadline, TimerHeap, TimerQueue};
+
+/// A virtual clock which only moves when it is told to.
+#[derive(Default)]
+pub struct SimulatedClock<T = Duration> {
+    now: Cell<T>,
+}
+
+impl<T: Copy> SimulatedClock<T> {
+    /// Create a clock starting at `now`.
+    pub const fn new(now: T) -> Self {
+        SimulatedClock {
+            now: Cell::new(now),
+        }
+    }
+
+    /// Set the current time.
+    pub fn set(&self, now: T) {
+        self.now.set(now);
+    }
+}
+
+impl<T: Copy> Clock<T> for SimulatedClock<T> {
+    fn now(&self) -> T {
+        self.now.get()
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SimulatedClock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulatedClock")
+            .field("now", &self.now.get())
+            .finish()
+    }
+}
+
+/// The error returned when a [`Simulator`] runs more steps than its limit.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepLimitExceeded;
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for StepLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("simulation step limit exceeded")
+    }
+}
+
+/// A deterministic driver of a timer on a [`SimulatedClock`].
+///
+/// Each step jumps the virtual time to the next timer and fires it,
+/// so timeouts are tested instantly and reproducibly.
+/// A run stops with [`StepLimitExceeded`] after too many steps,
+/// in case timers keep rescheduling themselves, even at the same time.
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use naive_timer::{Schedule, Simulator, TimerContext};
+///
+/// let mut sim = Simulator::new();
+/// sim.timer().add_after(Duration::from_secs(3), |now| {
+///     assert_eq!(now, Duration::from_secs(3));
+/// });
+///
+/// // nothing is due yet, but the time still moves
+/// assert_eq!(sim.run_for(Duration::from_secs(2)), Ok(0));
+/// assert_eq!(sim.now(), Duration::from_secs(2));
+///
+/// assert_eq!(sim.run_until_idle(), Ok(1));
+/// assert_eq!(sim.now(), Duration::from_secs(3));
+///
+/// // a periodic timer never becomes idle
+/// sim.timer().timer().add_periodic(
+///     Duration::from_secs(4),
+///     Duration::from_secs(1),
+///     Schedule::FixedRate,
+///     |_, _| {},
+/// );
+/// sim.set_step_limit(100);
+/// assert!(sim.run_until_idle().is_err());
+/// assert_eq!(sim.now(), Duration::from_secs(103));
+///
+/// // neither does a timer which keeps adding itself at the same time
+/// fn again(ctx: &mut TimerContext) {
+///     let now = ctx.now();
+///     ctx.add_with_context(now, again);
+/// }
+/// let mut sim = Simulator::new();
+/// sim.timer().timer().add_with_context(Duration::from_secs(1), again);
+/// sim.set_step_limit(100);
+/// assert!(sim.run_until_idle().is_err());
+/// assert_eq!(sim.now(), Duration::from_secs(1));
+/// ```
+#[cfg(feature = "alloc")]
+pub struct Simulator<T = Duration, Q = TimerHeap<T>> {
+    timer: ClockedTimer<SimulatedClock<T>, T, Q>,
+    step_limit: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Deadline> Simulator<T> {
+    /// Create a simulator starting at zero.
+    pub fn new() -> Self {
+        Simulator::with_queue(TimerHeap::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Deadline> Default for Simulator<T> {
+    fn default() -> Self {
+        Simulator::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Deadline, Q: TimerQueue<T>> Simulator<T, Q> {
+    /// The default limit of steps in a run.
+    pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+    /// Create a simulator starting at zero, which stores pending timers in `queue`.
+    pub fn with_queue(queue: Q) -> Self {
+        Simulator {
+            timer: ClockedTimer::with_queue(SimulatedClock::new(T::ZERO), queue),
+            step_limit: Self::DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// Set the limit of steps in a single run.
+    pub fn set_step_limit(&mut self, step_limit: usize) {
+        self.step_limit = step_limit;
+    }
+
+    /// The current virtual time.
+    pub fn now(&self) -> T {
+        self.timer.clock().now()
+    }
+
+    /// Get the timer driven by the virtual clock.
+    pub fn timer(&mut self) -> &mut ClockedTimer<SimulatedClock<T>, T, Q> {
+        &mut self.timer
+    }
+
+    /// Jump to the next timer and fire it.
+    ///
+    /// Other timers at the same time are fired by the following steps, in order.
+    /// Returns the new time, or `None` if there are no pending timers.
+    /// The time never moves backwards, so timers added in the past expire at once.
+    pub fn step(&mut self) -> Option<T> {
+        let now = self.timer.next()?.max(self.now());
+        self.timer.clock().set(now);
+        let _ = self.timer.timer().expire_bounded(now, 1);
+        Some(now)
+    }
+
+    /// Run all timers up to `deadline`, then move the time to `deadline`.
+    ///
+    /// Returns the number of steps.
+    pub fn run_until(&mut self, deadline: T) -> Result<usize, StepLimitExceeded> {
+        let steps = self.run_while(|next| next <= deadline)?;
+        if deadline > self.now() {
+            self.timer.clock().set(deadline);
+        }
+        Ok(steps)
+    }
+
+    /// Run all timers for `duration` from now. See [`run_until`](Simulator::run_until).
+    pub fn run_for(&mut self, duration: T) -> Result<usize, StepLimitExceeded> {
+        self.run_until(self.now() + duration)
+    }
+
+    /// Run until there are no pending timers.
+    ///
+    /// Returns the number of steps.
+    pub fn run_until_idle(&mut self) -> Result<usize, StepLimitExceeded> {
+        self.run_while(|_| true)
+    }
+
+    fn run_while(&mut self, f: impl Fn(T) -> bool) -> Result<usize, StepLimitExceeded> {
+        let mut steps = 0;
+        while let Some(next) = self.timer.next() {
+            if !f(next) {
+                break;
+            }
+            if steps == self.step_limit {
+                return Err(StepLimitExceeded);
+            }
+            self.step();
+            steps += 1;
+        }
+        Ok(steps)
+    }
+}
@@ -0,0 +1,253 @@
+use core::cell::RefCell;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+use crate::context::TimerOps;
+use crate::timer::Action;
+use crate::{Deadline, Interval, Schedule, Timer, TimerCallback, TimerHandle, TimerQueue};
+
+/// A timer shared between futures and the code which expires it.
+///
+/// It is implemented for `RefCell<Timer>`, for single-threaded executors
+/// which expire the timer outside of interrupt context.
+pub trait SharedTimer<T = Duration> {
+    /// The queue of the timer.
+    type Queue: TimerQueue<T>;
+
+    /// The callback type of the timer.
+    type Callback: TimerCallback<T>;
+
+    /// Call `f` with exclusive access to the timer.
+    fn with<R>(&self, f: impl FnOnce(&mut Timer<T, Self::Queue, Self::Callback>) -> R) -> R;
+
+    /// Create a future which completes once the timer is expired after `deadline`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::task::{Context, Poll, Wake, Waker};
+    /// use core::cell::RefCell;
+    /// use core::future::Future;
+    /// use core::pin::Pin;
+    /// use core::time::Duration;
+    /// use naive_timer::{SharedTimer, Timer};
+    ///
+    /// struct Flag(AtomicBool);
+    ///
+    /// impl Wake for Flag {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let flag = Arc::new(Flag(AtomicBool::new(false)));
+    /// let waker = Waker::from(flag.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// let mut sleep = timer.sleep_until(Duration::from_secs(1));
+    /// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+    ///
+    /// timer.borrow_mut().expire(Duration::from_secs(1));
+    /// assert!(flag.0.load(Ordering::SeqCst));
+    /// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Ready(()));
+    ///
+    /// // a dropped future leaves nothing behind
+    /// let mut sleep = timer.sleep_until(Duration::from_secs(2));
+    /// assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+    /// drop(sleep);
+    /// assert_eq!(timer.borrow().next(), None);
+    /// ```
+    fn sleep_until(&self, deadline: T) -> Sleep<'_, Self, T>
+    where
+        Self: Sized,
+        T: Ord + Copy,
+    {
+        Sleep {
+            timer: self,
+            deadline,
+            state: State::Idle,
+        }
+    }
+
+    /// Run `future` until the timer is expired after `deadline`.
+    ///
+    /// The future resolves to the output of `future`,
+    /// or [`Elapsed`] if the deadline has passed first.
+    ///
+    /// # Example
+    /// ```
+    /// use std::task::{Context, Poll, Waker};
+    /// use core::cell::RefCell;
+    /// use core::future::{self, Future};
+    /// use core::pin::Pin;
+    /// use core::time::Duration;
+    /// use naive_timer::{Elapsed, SharedTimer, Timer};
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// let mut ready = timer.timeout(Duration::from_secs(1), future::ready(42));
+    /// assert_eq!(Pin::new(&mut ready).poll(&mut cx), Poll::Ready(Ok(42)));
+    ///
+    /// let mut pending = timer.timeout(Duration::from_secs(1), future::pending::<()>());
+    /// assert_eq!(Pin::new(&mut pending).poll(&mut cx), Poll::Pending);
+    /// timer.borrow_mut().expire(Duration::from_secs(1));
+    /// assert_eq!(Pin::new(&mut pending).poll(&mut cx), Poll::Ready(Err(Elapsed)));
+    /// ```
+    fn timeout<F: Future>(&self, deadline: T, future: F) -> Timeout<'_, Self, F, T>
+    where
+        Self: Sized,
+        T: Ord + Copy,
+    {
+        Timeout {
+            future,
+            sleep: self.sleep_until(deadline),
+        }
+    }
+
+    /// Create a stream of ticks at `first_deadline` and then once per `period`,
+    /// with missed ticks handled according to the `schedule`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    fn interval(&self, first_deadline: T, period: T, schedule: Schedule) -> Interval<'_, Self, T>
+    where
+        Self: Sized,
+        T: Deadline + Send + Sync + 'static,
+    {
+        let handle = self.with(|timer| timer.add_ticker(first_deadline, period, schedule));
+        Interval::new(self, handle, period)
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>, F: TimerCallback<T>> SharedTimer<T>
+    for RefCell<Timer<T, Q, F>>
+{
+    type Queue = Q;
+    type Callback = F;
+
+    fn with<R>(&self, f: impl FnOnce(&mut Timer<T, Q, F>) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+impl<T: Ord + Copy, S: SharedTimer<T>> TimerOps<T> for &S {
+    fn insert(&mut self, deadline: T, action: Action<T>) -> TimerHandle {
+        self.with(|timer| timer.insert(deadline, action))
+    }
+
+    fn cancel(&mut self, handle: TimerHandle) -> bool {
+        // drop the callback after the timer is released
+        let action = self.with(|timer| timer.take(handle));
+        action.is_some()
+    }
+
+    fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        self.with(|timer| timer.reschedule(handle, deadline))
+    }
+}
+
+/// A future returned by [`SharedTimer::sleep_until`].
+///
+/// Its waker is registered with the timer on first poll,
+/// and deregistered if the future is dropped before completion.
+pub struct Sleep<'a, S: SharedTimer<T>, T: Ord + Copy = Duration> {
+    timer: &'a S,
+    deadline: T,
+    state: State,
+}
+
+enum State {
+    Idle,
+    Waiting(TimerHandle),
+    Done,
+}
+
+impl<S: SharedTimer<T>, T: Ord + Copy> Sleep<'_, S, T> {
+    /// The deadline of this future.
+    pub fn deadline(&self) -> T {
+        self.deadline
+    }
+}
+
+// The deadline is never pinned.
+impl<S: SharedTimer<T>, T: Ord + Copy> Unpin for Sleep<'_, S, T> {}
+
+impl<S: SharedTimer<T>, T: Ord + Copy> Future for Sleep<'_, S, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        match this.state {
+            State::Idle => {
+                let (deadline, waker) = (this.deadline, cx.waker().clone());
+                let handle = this.timer.with(|timer| timer.add_waker(deadline, waker));
+                this.state = State::Waiting(handle);
+                Poll::Pending
+            }
+            State::Waiting(handle) => {
+                if this
+                    .timer
+                    .with(|timer| timer.register_waker(handle, cx.waker()))
+                {
+                    Poll::Pending
+                } else {
+                    this.state = State::Done;
+                    Poll::Ready(())
+                }
+            }
+            State::Done => Poll::Ready(()),
+        }
+    }
+}
+
+impl<S: SharedTimer<T>, T: Ord + Copy> Drop for Sleep<'_, S, T> {
+    fn drop(&mut self) {
+        if let State::Waiting(handle) = self.state {
+            self.timer.with(|timer| timer.cancel(handle));
+        }
+    }
+}
+
+/// The error returned when a [`Timeout`] has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+/// A future returned by [`SharedTimer::timeout`].
+pub struct Timeout<'a, S: SharedTimer<T>, F, T: Ord + Copy = Duration> {
+    future: F,
+    sleep: Sleep<'a, S, T>,
+}
+
+impl<S: SharedTimer<T>, F, T: Ord + Copy> Timeout<'_, S, F, T> {
+    /// The deadline of this future.
+    pub fn deadline(&self) -> T {
+        self.sleep.deadline()
+    }
+}
+
+impl<S: SharedTimer<T>, F: Future, T: Ord + Copy> Future for Timeout<'_, S, F, T> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned and never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut this.sleep).poll(cx).map(|()| Err(Elapsed))
+    }
+}
@@ -0,0 +1,159 @@
+use core::fmt;
+use core::time::Duration;
+
+use crate::TimerHandle;
+
+/// A fixed-capacity timer which never allocates.
+///
+/// Up to `N` timers are stored inline. Instead of boxed closures,
+/// a timer's callback is a plain function pointer, which is called with
+/// the current time and a `usize` context given on [`try_add`](StaticTimer::try_add).
+///
+/// Deadlines are of type `T`, which is `Duration` by default.
+///
+/// Adding and expiring a timer cost O(N), so `N` is meant to be small.
+///
+/// # Example
+/// ```
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::StaticTimer;
+///
+/// static FIRED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn on_timeout(_now: Duration, id: usize) {
+///     FIRED.store(id, Ordering::SeqCst);
+/// }
+///
+/// let mut timer = StaticTimer::<2>::new();
+/// timer.try_add(Duration::from_secs(1), on_timeout, 1).unwrap();
+/// timer.try_add(Duration::from_secs(2), on_timeout, 2).unwrap();
+/// assert!(timer.try_add(Duration::from_secs(3), on_timeout, 3).is_err());
+///
+/// timer.expire(Duration::from_secs(1));
+/// assert_eq!(FIRED.load(Ordering::SeqCst), 1);
+/// assert_eq!(timer.next(), Some(Duration::from_secs(2)));
+/// ```
+pub struct StaticTimer<const N: usize, T = Duration> {
+    events: [Option<Event<T>>; N],
+    /// Incremented every time a slot is freed, to detect stale handles.
+    generations: [u32; N],
+    /// The sequence number of the next added timer.
+    seq: u64,
+}
+
+/// The error returned when a [`StaticTimer`] is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerFull;
+
+impl fmt::Display for TimerFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("timer is full")
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Event<T> {
+    deadline: T,
+    seq: u64,
+    callback: fn(T, usize),
+    context: usize,
+}
+
+impl<const N: usize, T: Ord + Copy> Default for StaticTimer<N, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, T: Ord + Copy> StaticTimer<N, T> {
+    /// Create an empty timer.
+    pub const fn new() -> Self {
+        StaticTimer {
+            events: [None; N],
+            generations: [0; N],
+            seq: 0,
+        }
+    }
+
+    /// Add a timer.
+    ///
+    /// The `callback` will be called with `context` on timer expired after `deadline`.
+    ///
+    /// Returns a handle which can be used to cancel the timer,
+    /// or [`TimerFull`] if there are already `N` pending timers.
+    pub fn try_add(
+        &mut self,
+        deadline: T,
+        callback: fn(T, usize),
+        context: usize,
+    ) -> Result<TimerHandle, TimerFull> {
+        let index = self
+            .events
+            .iter()
+            .position(|e| e.is_none())
+            .ok_or(TimerFull)?;
+        self.seq += 1;
+        self.events[index] = Some(Event {
+            deadline,
+            seq: self.seq,
+            callback,
+            context,
+        });
+        Ok(TimerHandle {
+            index,
+            generation: self.generations[index],
+        })
+    }
+
+    /// Cancel a timer.
+    ///
+    /// Returns `false` if the timer has already been fired or cancelled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        match self.generations.get(handle.index) {
+            Some(&generation) if generation == handle.generation => {
+                self.release(handle.index).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Expire timers.
+    ///
+    /// Given the current time `now`, trigger and remove all expired timers.
+    ///
+    /// Timers are triggered in the order of their deadlines.
+    /// Timers with the same deadline are triggered in the order they were added.
+    pub fn expire(&mut self, now: T) {
+        while let Some(index) = self.first() {
+            let event = self.events[index].unwrap();
+            if event.deadline > now {
+                break;
+            }
+            self.release(index);
+            (event.callback)(now, event.context);
+        }
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.first().map(|i| self.events[i].unwrap().deadline)
+    }
+
+    /// Get the index of the earliest timer.
+    fn first(&self) -> Option<usize> {
+        (0..N)
+            .filter(|&i| self.events[i].is_some())
+            .min_by_key(|&i| {
+                let event = self.events[i].unwrap();
+                (event.deadline, event.seq)
+            })
+    }
+
+    /// Free the slot at `index`, returning its event.
+    fn release(&mut self, index: usize) -> Option<Event<T>> {
+        let event = self.events[index].take()?;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        Some(event)
+    }
+}
@@ -0,0 +1,171 @@
+use core::cell::RefCell;
+use core::time::Duration;
+
+use critical_section::Mutex;
+
+use crate::context::TimerOps;
+use crate::timer::{budget_spent, Action};
+use crate::{
+    Clock, Deadline, ExpireResult, ExpiredTimer, Schedule, SharedTimer, Timer, TimerContext,
+    TimerHandle, TimerHeap, TimerQueue,
+};
+
+/// A timer which can be shared between threads and interrupt handlers.
+///
+/// Every operation takes `&self` and accesses the timer in a critical section
+/// of the [`critical_section`] crate, so adding a timer from thread context
+/// can not deadlock with [`expire`](SyncTimer::expire) from the timer interrupt.
+///
+/// Callbacks are called outside of the critical section,
+/// and they are dropped outside of it when cancelled.
+///
+/// It implements [`SharedTimer`], so it can drive futures as well.
+///
+/// # Example
+/// ```
+/// use alloc::sync::Arc;
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::SyncTimer;
+/// extern crate alloc;
+///
+/// static TIMER: SyncTimer = SyncTimer::new();
+/// let event = Arc::new(AtomicBool::new(false));
+///
+/// TIMER.add_with_context(Duration::from_secs(1), {
+///     let event = event.clone();
+///     move |ctx| {
+///         // the timer can be used from the callback
+///         TIMER.add(ctx.now(), move |_| event.store(true, Ordering::SeqCst));
+///     }
+/// });
+///
+/// // usually from the timer interrupt
+/// TIMER.expire(Duration::from_secs(1));
+/// assert_eq!(event.load(Ordering::SeqCst), true);
+/// assert_eq!(TIMER.next(), None);
+/// ```
+pub struct SyncTimer<T = Duration, Q = TimerHeap<T>> {
+    inner: Mutex<RefCell<Timer<T, Q>>>,
+}
+
+impl<T: Ord + Copy> SyncTimer<T> {
+    /// Create an empty timer.
+    pub const fn new() -> Self {
+        SyncTimer::with_timer(Timer::with_queue(TimerHeap::new()))
+    }
+}
+
+impl<T: Ord + Copy> Default for SyncTimer<T> {
+    fn default() -> Self {
+        SyncTimer::new()
+    }
+}
+
+impl<T, Q> SyncTimer<T, Q> {
+    /// Create a timer from `timer`.
+    pub const fn with_timer(timer: Timer<T, Q>) -> Self {
+        SyncTimer {
+            inner: Mutex::new(RefCell::new(timer)),
+        }
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>> SyncTimer<T, Q> {
+    /// Add a timer. See [`Timer::add`].
+    pub fn add(
+        &self,
+        deadline: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.with(|timer| timer.add(deadline, callback))
+    }
+
+    /// Add a timer with context. See [`Timer::add_with_context`].
+    pub fn add_with_context(
+        &self,
+        deadline: T,
+        callback: impl FnOnce(&mut TimerContext<T>) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.with(|timer| timer.add_with_context(deadline, callback))
+    }
+
+    /// Cancel a timer. See [`Timer::cancel`].
+    pub fn cancel(&self, handle: TimerHandle) -> bool {
+        let action = self.with(|timer| timer.take(handle));
+        action.is_some()
+    }
+
+    /// Reschedule a timer. See [`Timer::reschedule`].
+    pub fn reschedule(&self, handle: TimerHandle, deadline: T) -> bool {
+        self.with(|timer| timer.reschedule(handle, deadline))
+    }
+
+    /// Expire timers. See [`Timer::expire`].
+    ///
+    /// Each expired timer is removed in a critical section,
+    /// and its callback is called after the critical section.
+    pub fn expire(&self, now: T) {
+        while let Some(callback) = self.with(|timer| timer.pop_expired(now)) {
+            ExpiredTimer::new(callback, now).call(self);
+        }
+    }
+
+    /// Expire timers, firing at most `max_events` of them. See [`Timer::expire_bounded`].
+    ///
+    /// As [`SyncTimer::expire`], each callback is called after the critical section.
+    pub fn expire_bounded(&self, now: T, max_events: usize) -> ExpireResult {
+        for _ in 0..max_events {
+            match self.with(|timer| timer.pop_expired(now)) {
+                Some(callback) => ExpiredTimer::new(callback, now).call(self),
+                None => return ExpireResult::Done,
+            }
+        }
+        self.with(|timer| ExpireResult::after(timer, now))
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.with(|timer| timer.next())
+    }
+}
+
+impl<T: Deadline, Q: TimerQueue<T>> SyncTimer<T, Q> {
+    /// Expire timers, until `budget` has passed on `clock` since the call.
+    /// See [`Timer::expire_with_budget`].
+    ///
+    /// As [`SyncTimer::expire`], each callback is called after the critical section.
+    pub fn expire_with_budget<C: Clock<T>>(&self, now: T, clock: &C, budget: T) -> ExpireResult {
+        let start = clock.now();
+        while let Some(callback) = self.with(|timer| timer.pop_expired(now)) {
+            ExpiredTimer::new(callback, now).call(self);
+            if budget_spent(clock, start, budget) {
+                return self.with(|timer| ExpireResult::after(timer, now));
+            }
+        }
+        ExpireResult::Done
+    }
+}
+
+impl<T: Deadline + Send + Sync + 'static, Q: TimerQueue<T>> SyncTimer<T, Q> {
+    /// Add a periodic timer. See [`Timer::add_periodic`].
+    pub fn add_periodic(
+        &self,
+        first_deadline: T,
+        interval: T,
+        schedule: Schedule,
+        callback: impl FnMut(T, u64) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        let action = Action::periodic(interval, schedule, callback);
+        self.with(|timer| timer.insert(first_deadline, action))
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>> SharedTimer<T> for SyncTimer<T, Q> {
+    type Queue = Q;
+    type Callback = crate::Callback<T>;
+
+    fn with<R>(&self, f: impl FnOnce(&mut Timer<T, Q>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.inner.borrow_ref_mut(cs)))
+    }
+}
@@ -0,0 +1,927 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::task::Waker;
+use core::time::Duration;
+
+use crate::context::TimerOps;
+use crate::{
+    Clock, Deadline, PayloadTimer, SharedTimer, TimerContext, TimerHandle, TimerHeap, TimerQueue,
+};
+
+/// A naive timer.
+///
+/// Deadlines are of type `T`, which can be any `Ord + Copy` type,
+/// such as `Duration` (by default) or `u64` ticks of a hardware counter.
+///
+/// Pending timers are stored in a [`TimerQueue`] `Q`, which is a [`TimerHeap`] by default.
+///
+/// Callbacks of type `F` can be added by [`Timer::add_callback`] without being boxed.
+/// See [`TimerCallback`].
+///
+/// # Example
+/// ```
+/// use naive_timer::Timer;
+///
+/// // deadlines in cycles
+/// let mut timer = Timer::<u64>::default();
+/// timer.add(1_000_000, |now| assert_eq!(now, 1_000_500));
+///
+/// timer.expire(1_000_500);
+/// assert_eq!(timer.next(), None);
+/// ```
+pub struct Timer<T = Duration, Q = TimerHeap<T>, F = Callback<T>> {
+    timers: PayloadTimer<Entry<T, F>, T, Q>,
+}
+
+/// A callback of timers added by [`Timer::add_callback`].
+///
+/// Implementing it on an enum of events allows them to be stored in the timer inline,
+/// and called without dynamic dispatch.
+///
+/// # Example
+/// ```
+/// use core::sync::atomic::{AtomicU32, Ordering};
+/// use core::time::Duration;
+/// use naive_timer::{Timer, TimerCallback, TimerHeap};
+///
+/// static RETRANSMITS: AtomicU32 = AtomicU32::new(0);
+///
+/// enum Event {
+///     Retransmit(u32),
+///     Close(u32),
+/// }
+///
+/// impl TimerCallback for Event {
+///     fn call(self, _now: Duration) {
+///         if let Event::Retransmit(_conn) = self {
+///             RETRANSMITS.fetch_add(1, Ordering::SeqCst);
+///         }
+///     }
+/// }
+///
+/// let mut timer = Timer::<Duration, TimerHeap, Event>::with_callback_queue(TimerHeap::new());
+/// timer.add_callback(Duration::from_secs(1), Event::Retransmit(1));
+/// timer.add_callback(Duration::from_secs(2), Event::Close(1));
+///
+/// timer.expire(Duration::from_secs(1));
+/// assert_eq!(RETRANSMITS.load(Ordering::SeqCst), 1);
+/// assert_eq!(timer.next(), Some(Duration::from_secs(2)));
+/// ```
+pub trait TimerCallback<T = Duration> {
+    /// Call the callback on timer expired at `now`.
+    fn call(self, now: T);
+}
+
+/// The default type of callbacks of [`Timer::add_callback`].
+pub type Callback<T = Duration> = Box<dyn FnOnce(T) + Send + Sync + 'static>;
+
+impl<T> TimerCallback<T> for Callback<T> {
+    fn call(self, now: T) {
+        self(now)
+    }
+}
+
+/// The type of callback function with context.
+pub(crate) type ContextCallback<T> = Box<dyn FnOnce(&mut TimerContext<T>) + Send + Sync + 'static>;
+
+/// The type of periodic callback function.
+///
+/// It is called with the deadline and the current time, and returns the next deadline.
+pub(crate) type PeriodicCallback<T> = Box<dyn FnMut(T, T) -> T + Send + Sync + 'static>;
+
+/// The type of function computing the next tick of a ticker.
+///
+/// It is called with the deadline and the current time,
+/// and returns the next deadline and the number of ticks due.
+pub(crate) type TickerCallback<T> = Box<dyn Fn(T, T) -> (T, u64) + Send + Sync + 'static>;
+
+/// How a periodic timer computes its next deadline after fired.
+///
+/// The schedules differ only when the timer is expired late, that is,
+/// when [`Timer::expire`] is called one or more periods after the deadline.
+///
+/// # Example
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use core::time::Duration;
+/// use naive_timer::{Schedule, Timer};
+///
+/// let mut timer = Timer::default();
+/// let overruns = Arc::new(Mutex::new(Vec::new()));
+///
+/// timer.add_periodic(Duration::from_secs(1), Duration::from_secs(1), Schedule::Aligned, {
+///     let overruns = overruns.clone();
+///     move |_now, overrun| overruns.lock().unwrap().push(overrun)
+/// });
+///
+/// // fire once, skipping the periods at 2s and 3s
+/// timer.expire(Duration::from_millis(3500));
+/// assert_eq!(*overruns.lock().unwrap(), [2]);
+/// assert_eq!(timer.next(), Some(Duration::from_secs(4)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Schedule {
+    /// The next deadline is the previous deadline plus the interval.
+    ///
+    /// If the timer is expired late, it fires once for every missed period.
+    FixedRate,
+    /// The next deadline is the first `deadline + k * interval` after `now`.
+    ///
+    /// If the timer is expired late, it fires once and skips the missed periods.
+    Aligned,
+    /// The next deadline is the current time `now` plus the interval.
+    ///
+    /// If the timer is expired late, it fires once and restarts from `now`.
+    FixedDelay,
+}
+
+/// The result of expiring a bounded number of timers, such as by [`Timer::expire_bounded`].
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExpireResult {
+    /// All expired timers are fired.
+    Done,
+    /// Some expired timers are not fired yet.
+    ///
+    /// They are fired by the next call, before timers expired later.
+    Remaining,
+}
+
+impl ExpireResult {
+    /// Whether expired timers remain in `timer` after the limit is reached at `now`.
+    pub(crate) fn after<T: Ord + Copy, Q: TimerQueue<T>, F: TimerCallback<T>>(
+        timer: &Timer<T, Q, F>,
+        now: T,
+    ) -> Self {
+        match timer.next() {
+            Some(deadline) if deadline <= now => ExpireResult::Remaining,
+            _ => ExpireResult::Done,
+        }
+    }
+}
+
+impl<T: Ord + Copy> Default for Timer<T> {
+    fn default() -> Self {
+        Timer::with_queue(TimerHeap::default())
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>> Timer<T, Q> {
+    /// Create a timer which stores pending timers in `queue`.
+    pub const fn with_queue(queue: Q) -> Self {
+        Timer::with_callback_queue(queue)
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>, F: TimerCallback<T>> Timer<T, Q, F> {
+    /// Create a timer with callbacks of type `F`, which stores pending timers in `queue`.
+    pub const fn with_callback_queue(queue: Q) -> Self {
+        Timer {
+            timers: PayloadTimer::with_queue(queue),
+        }
+    }
+
+    /// Add a timer.
+    ///
+    /// The `callback` will be called on timer expired after `deadline`.
+    ///
+    /// Returns a handle which can be used to cancel the timer.
+    pub fn add(
+        &mut self,
+        deadline: T,
+        callback: impl FnOnce(T) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.insert(deadline, Action::once(callback))
+    }
+
+    /// Add a timer with a `callback` of type `F`.
+    ///
+    /// Unlike [`Timer::add`], the callback is stored in the timer without being boxed,
+    /// and called without dynamic dispatch. See [`TimerCallback`].
+    ///
+    /// Returns a handle which can be used to cancel the timer.
+    pub fn add_callback(&mut self, deadline: T, callback: F) -> TimerHandle {
+        self.timers.add(deadline, Entry::Callback(callback))
+    }
+
+    /// Add a timer whose callback can access the timer.
+    ///
+    /// The `callback` will be called with a [`TimerContext`] on timer expired after `deadline`.
+    /// It can add, cancel and reschedule timers through the context.
+    /// Timers added with a deadline not after `now` are fired in the same [`Timer::expire`],
+    /// after the current callback returns, in the order of their deadlines.
+    ///
+    /// # Example
+    /// ```
+    /// use alloc::sync::Arc;
+    /// use core::time::Duration;
+    /// use core::sync::atomic::{AtomicBool, Ordering};
+    /// use naive_timer::Timer;
+    /// extern crate alloc;
+    ///
+    /// let mut timer = Timer::default();
+    /// let event = Arc::new(AtomicBool::new(false));
+    ///
+    /// timer.add_with_context(Duration::from_secs(1), {
+    ///     let event = event.clone();
+    ///     move |ctx| {
+    ///         // add a follow-up timer which is already due
+    ///         let deadline = ctx.now();
+    ///         ctx.add(deadline, move |_| event.store(true, Ordering::SeqCst));
+    ///     }
+    /// });
+    ///
+    /// timer.expire(Duration::from_secs(1));
+    /// assert_eq!(event.load(Ordering::SeqCst), true);
+    /// assert_eq!(timer.next(), None);
+    /// ```
+    pub fn add_with_context(
+        &mut self,
+        deadline: T,
+        callback: impl FnOnce(&mut TimerContext<T>) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        self.insert(deadline, Action::once_with_context(callback))
+    }
+
+    /// Cancel a timer.
+    ///
+    /// The callback is dropped without being called.
+    /// Returns `false` if the timer has already been fired or cancelled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.take(handle).is_some()
+    }
+
+    /// Reschedule a timer to a new `deadline`.
+    ///
+    /// The timer keeps its callback and handle.
+    /// Returns `false` if the timer has already been fired or cancelled.
+    ///
+    /// # Example
+    /// ```
+    /// use core::time::Duration;
+    /// use naive_timer::Timer;
+    ///
+    /// let mut timer = Timer::default();
+    /// let handle = timer.add(Duration::from_secs(1), |_| {});
+    ///
+    /// assert!(timer.reschedule(handle, Duration::from_secs(3)));
+    /// timer.expire(Duration::from_secs(2));
+    /// assert_eq!(timer.next(), Some(Duration::from_secs(3)));
+    /// ```
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        self.timers.reschedule(handle, deadline)
+    }
+
+    /// Expire timers.
+    ///
+    /// Given the current time `now`, trigger and remove all expired timers.
+    /// Periodic timers are triggered and then added back with their next deadline.
+    ///
+    /// Timers are triggered in the order of their deadlines.
+    /// Timers with the same deadline are triggered in the order they were added
+    /// (or rescheduled).
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use core::time::Duration;
+    /// use naive_timer::Timer;
+    ///
+    /// let mut timer = Timer::default();
+    /// let order = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// for i in 0..4 {
+    ///     let order = order.clone();
+    ///     let deadline = Duration::from_secs(if i == 2 { 1 } else { 2 });
+    ///     timer.add(deadline, move |_| order.lock().unwrap().push(i));
+    /// }
+    ///
+    /// timer.expire(Duration::from_secs(2));
+    /// assert_eq!(*order.lock().unwrap(), [2, 0, 1, 3]);
+    /// ```
+    pub fn expire(&mut self, now: T) {
+        while let Some(expired) = self.pop_expired(now) {
+            self.fire(expired, now);
+        }
+    }
+
+    /// Expire timers, firing at most `max_events` of them.
+    ///
+    /// Every expired timer counts, including a tick of an [`Interval`](crate::Interval)
+    /// which no task waits for. Timers which are not fired stay in the timer in order,
+    /// and they are fired first by the next call.
+    /// Returns [`ExpireResult::Remaining`] if some expired timers are not fired yet.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use core::time::Duration;
+    /// use naive_timer::{ExpireResult, Timer};
+    ///
+    /// let mut timer = Timer::default();
+    /// let order = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// for i in 0..3 {
+    ///     let order = order.clone();
+    ///     timer.add(Duration::from_secs(1), move |_| order.lock().unwrap().push(i));
+    /// }
+    ///
+    /// let now = Duration::from_secs(1);
+    /// assert_eq!(timer.expire_bounded(now, 2), ExpireResult::Remaining);
+    /// assert_eq!(*order.lock().unwrap(), [0, 1]);
+    /// assert_eq!(timer.expire_bounded(now, 2), ExpireResult::Done);
+    /// assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+    /// ```
+    ///
+    /// The ticks of an interval which is not polled count as well:
+    /// ```
+    /// use alloc::sync::Arc;
+    /// use core::cell::RefCell;
+    /// use core::sync::atomic::{AtomicBool, Ordering};
+    /// use core::time::Duration;
+    /// use naive_timer::{ExpireResult, Schedule, SharedTimer, Timer};
+    /// extern crate alloc;
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let fired = Arc::new(AtomicBool::new(false));
+    ///
+    /// let period = Duration::from_millis(1);
+    /// let _interval = timer.interval(period, period, Schedule::FixedRate);
+    /// timer.borrow_mut().add(period, {
+    ///     let fired = fired.clone();
+    ///     move |_| fired.store(true, Ordering::SeqCst)
+    /// });
+    ///
+    /// let now = Duration::from_secs(1000);
+    /// let result = timer.borrow_mut().expire_bounded(now, 1);
+    /// assert_eq!(result, ExpireResult::Remaining);
+    /// assert_eq!(fired.load(Ordering::SeqCst), false);
+    /// assert_eq!(timer.borrow_mut().expire_bounded(now, 1), ExpireResult::Done);
+    /// assert_eq!(fired.load(Ordering::SeqCst), true);
+    /// ```
+    pub fn expire_bounded(&mut self, now: T, max_events: usize) -> ExpireResult {
+        for _ in 0..max_events {
+            match self.pop_expired(now) {
+                Some(expired) => self.fire(expired, now),
+                None => return ExpireResult::Done,
+            }
+        }
+        ExpireResult::after(self, now)
+    }
+
+    /// Fire an `expired` timer, which is removed by [`Timer::pop_expired`].
+    fn fire(&mut self, expired: Expired<T, F>, now: T) {
+        match expired {
+            Expired::Once(callback) => callback(&mut TimerContext { timer: self, now }),
+            Expired::Callback(callback) => callback.call(now),
+            Expired::Wake(waker) => waker.wake(),
+            Expired::Tick => {}
+            Expired::Periodic {
+                handle,
+                deadline,
+                mut callback,
+            } => {
+                let next = callback(deadline, now);
+                self.restore_periodic(handle, callback, next);
+            }
+        }
+    }
+
+    /// Move all pending timers from `other` into `self`.
+    ///
+    /// The callbacks are moved as they are, without being boxed again.
+    /// Timers of `other` are ordered after timers of `self` with the same deadline.
+    ///
+    /// The moved timers get new handles in `self`, so the old and new handles
+    /// are returned for the caller to update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has timers of futures such as [`Sleep`](crate::Sleep),
+    /// which poll them through `other` and can not follow them. Nothing is moved then.
+    /// [`PerCpuTimer::migrate_all`](crate::PerCpuTimer::migrate_all) hands such timers
+    /// over to another CPU without moving them.
+    ///
+    /// # Example
+    /// ```
+    /// use core::time::Duration;
+    /// use naive_timer::Timer;
+    ///
+    /// let mut timer = Timer::default();
+    /// let mut other = Timer::default();
+    /// let handle = other.add(Duration::from_secs(1), |_| {});
+    ///
+    /// let moved = timer.append(&mut other);
+    /// assert_eq!(other.next(), None);
+    /// assert_eq!(timer.next(), Some(Duration::from_secs(1)));
+    ///
+    /// let (old, new) = moved[0];
+    /// assert_eq!(old, handle);
+    /// assert!(timer.cancel(new));
+    /// ```
+    ///
+    /// A pending future can not be moved:
+    /// ```should_panic
+    /// use std::task::{Context, Waker};
+    /// use core::cell::RefCell;
+    /// use core::future::Future;
+    /// use core::pin::Pin;
+    /// use core::time::Duration;
+    /// use naive_timer::{SharedTimer, Timer};
+    ///
+    /// let mut timer = Timer::default();
+    /// let other = RefCell::new(Timer::default());
+    /// let mut sleep = other.sleep_until(Duration::from_secs(1));
+    /// let _ = Pin::new(&mut sleep).poll(&mut Context::from_waker(Waker::noop()));
+    ///
+    /// timer.append(&mut other.borrow_mut());
+    /// ```
+    pub fn append<Q2: TimerQueue<T>>(
+        &mut self,
+        other: &mut Timer<T, Q2, F>,
+    ) -> Vec<(TimerHandle, TimerHandle)> {
+        let future = |entry: &Entry<T, F>| {
+            matches!(entry, Entry::Action(Action::Wake(_) | Action::Tick { .. }))
+        };
+        assert!(
+            !other.timers.any(future),
+            "timers of futures can not be moved"
+        );
+        self.timers.append(&mut other.timers)
+    }
+
+    /// Remove expired timers, returning their callbacks to be called later.
+    ///
+    /// This allows the caller to release the lock around the timer before calling the callbacks.
+    /// The callbacks are returned in the order [`Timer::expire`] would call them.
+    ///
+    /// A periodic timer is rescheduled when its callback is called,
+    /// so every returned [`ExpiredTimer`] should be called.
+    ///
+    /// Until then, the next period of a periodic timer is not due,
+    /// and timers added by the callbacks are not in the timer either.
+    /// To fire all timers as [`Timer::expire`] does, call it again after calling the callbacks,
+    /// until nothing is returned. The callbacks taken by each call
+    /// are called after those taken by the previous one.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use core::cell::RefCell;
+    /// use core::time::Duration;
+    /// use naive_timer::Timer;
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let order = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// for i in 0..3 {
+    ///     let order = order.clone();
+    ///     let deadline = Duration::from_secs(3 - i);
+    ///     timer.borrow_mut().add(deadline, move |_| order.lock().unwrap().push(i));
+    /// }
+    ///
+    /// let expired = timer.borrow_mut().take_expired(Duration::from_secs(2));
+    /// assert_eq!(expired.len(), 2);
+    /// assert!(order.lock().unwrap().is_empty());
+    ///
+    /// // the timer is no longer borrowed
+    /// for callback in expired {
+    ///     callback.call(&timer);
+    /// }
+    /// assert_eq!(*order.lock().unwrap(), [2, 1]);
+    /// ```
+    ///
+    /// A periodic timer which is expired late fires once for each call:
+    /// ```
+    /// use alloc::sync::Arc;
+    /// use core::cell::RefCell;
+    /// use core::sync::atomic::{AtomicU32, Ordering};
+    /// use core::time::Duration;
+    /// use naive_timer::{Schedule, Timer};
+    /// extern crate alloc;
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let count = Arc::new(AtomicU32::new(0));
+    ///
+    /// let period = Duration::from_secs(1);
+    /// timer.borrow_mut().add_periodic(period, period, Schedule::FixedRate, {
+    ///     let count = count.clone();
+    ///     move |_, _| {
+    ///         count.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    /// });
+    ///
+    /// // the periods at 1s, 2s and 3s are due
+    /// let now = Duration::from_millis(3500);
+    /// loop {
+    ///     let expired = timer.borrow_mut().take_expired(now);
+    ///     if expired.is_empty() {
+    ///         break;
+    ///     }
+    ///     for callback in expired {
+    ///         callback.call(&timer);
+    ///     }
+    /// }
+    /// assert_eq!(count.load(Ordering::SeqCst), 3);
+    /// assert_eq!(timer.borrow().next(), Some(Duration::from_secs(4)));
+    /// ```
+    pub fn take_expired(&mut self, now: T) -> Vec<ExpiredTimer<T, F>> {
+        let mut expired = Vec::new();
+        while let Some(callback) = self.pop_expired(now) {
+            expired.push(ExpiredTimer::new(callback, now));
+        }
+        expired
+    }
+
+    /// Get next timer.
+    pub fn next(&self) -> Option<T> {
+        self.timers.next()
+    }
+
+    /// Add a timer which wakes `waker` on expired.
+    pub(crate) fn add_waker(&mut self, deadline: T, waker: Waker) -> TimerHandle {
+        self.insert(deadline, Action::Wake(waker))
+    }
+
+    /// Replace the waker of a pending timer added by [`Timer::add_waker`].
+    ///
+    /// Returns `false` if the timer has already been fired or cancelled.
+    pub(crate) fn register_waker(&mut self, handle: TimerHandle, waker: &Waker) -> bool {
+        match self.timers.get_mut(handle) {
+            Some(Entry::Action(Action::Wake(old))) => {
+                if !old.will_wake(waker) {
+                    *old = waker.clone();
+                }
+                true
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Take a tick of a timer added by [`Timer::add_ticker`],
+    /// or register `waker` to be woken on the next tick.
+    ///
+    /// Returns `true` if a tick is taken.
+    pub(crate) fn poll_ticker(&mut self, handle: TimerHandle, waker: &Waker) -> bool {
+        match self.timers.get_mut(handle) {
+            Some(Entry::Action(Action::Tick { ticks, .. })) if *ticks > 0 => {
+                *ticks -= 1;
+                true
+            }
+            Some(Entry::Action(Action::Tick { waker: old, .. })) => {
+                if !old.as_ref().is_some_and(|old| old.will_wake(waker)) {
+                    *old = Some(waker.clone());
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove a pending timer, returning its entry.
+    pub(crate) fn take(&mut self, handle: TimerHandle) -> Option<Entry<T, F>> {
+        self.timers.cancel(handle)
+    }
+
+    /// Remove the first expired timer at `now`, returning its callback to be called.
+    ///
+    /// A periodic timer keeps its slot, and is not in the queue
+    /// until its callback is given back by [`Timer::restore_periodic`].
+    ///
+    /// A ticker is rescheduled at once. It is returned even if no task waits for its ticks,
+    /// so that callers count it and release their locks between expired timers.
+    pub(crate) fn pop_expired(&mut self, now: T) -> Option<Expired<T, F>> {
+        let (deadline, handle) = self.timers.pop_key(now)?;
+        Some(match self.timers.get_mut(handle).unwrap() {
+            Entry::Action(Action::Periodic(callback)) => Expired::Periodic {
+                handle,
+                deadline,
+                callback: callback.take().unwrap(),
+            },
+            Entry::Action(Action::Tick { next, waker, ticks }) => {
+                let (next, due) = next(deadline, now);
+                *ticks = ticks.saturating_add(due);
+                let waker = waker.take();
+                self.timers.push_back(handle, next);
+                waker.map_or(Expired::Tick, Expired::Wake)
+            }
+            _ => match self.timers.release(handle.index) {
+                Entry::Action(Action::Once(callback)) => Expired::Once(callback),
+                Entry::Action(Action::Wake(waker)) => Expired::Wake(waker),
+                Entry::Callback(callback) => Expired::Callback(callback),
+                _ => unreachable!(),
+            },
+        })
+    }
+
+    /// Give back the `callback` of a periodic timer taken by [`Timer::pop_expired`],
+    /// and schedule it at `next`.
+    ///
+    /// The callback is dropped if the timer has been cancelled meanwhile.
+    pub(crate) fn restore_periodic(
+        &mut self,
+        handle: TimerHandle,
+        callback: PeriodicCallback<T>,
+        next: T,
+    ) {
+        if let Some(Entry::Action(Action::Periodic(slot @ None))) = self.timers.get_mut(handle) {
+            *slot = Some(callback);
+            self.timers.push_back(handle, next);
+        }
+    }
+}
+
+impl<T: Deadline, Q: TimerQueue<T>, F: TimerCallback<T>> Timer<T, Q, F> {
+    /// Expire timers, until `budget` has passed on `clock` since the call.
+    ///
+    /// The clock is checked after each fired timer, including a tick of an
+    /// [`Interval`](crate::Interval) which no task waits for, so at least one expired timer
+    /// is fired and repeated calls make progress. Timers which are not fired stay in the timer in order,
+    /// and they are fired first by the next call.
+    /// Returns [`ExpireResult::Remaining`] if some expired timers are not fired yet.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use core::sync::atomic::{AtomicU64, Ordering};
+    /// use core::time::Duration;
+    /// use naive_timer::{ExpireResult, Timer};
+    ///
+    /// let mut timer = Timer::default();
+    /// let order = Arc::new(Mutex::new(Vec::new()));
+    /// let millis = Arc::new(AtomicU64::new(0));
+    /// let clock = || Duration::from_millis(millis.load(Ordering::SeqCst));
+    ///
+    /// // each callback takes 10ms
+    /// for i in 0..4 {
+    ///     let (order, millis) = (order.clone(), millis.clone());
+    ///     timer.add(Duration::from_secs(1), move |_| {
+    ///         millis.fetch_add(10, Ordering::SeqCst);
+    ///         order.lock().unwrap().push(i);
+    ///     });
+    /// }
+    ///
+    /// let (now, budget) = (Duration::from_secs(1), Duration::from_millis(25));
+    /// let result = timer.expire_with_budget(now, &clock, budget);
+    /// assert_eq!(result, ExpireResult::Remaining);
+    /// assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+    ///
+    /// let result = timer.expire_with_budget(now, &clock, budget);
+    /// assert_eq!(result, ExpireResult::Done);
+    /// assert_eq!(*order.lock().unwrap(), [0, 1, 2, 3]);
+    /// ```
+    ///
+    /// With a zero budget, a single timer is fired per call:
+    /// ```
+    /// use core::cell::RefCell;
+    /// use core::time::Duration;
+    /// use naive_timer::{ExpireResult, Schedule, SharedTimer, Timer};
+    ///
+    /// let timer = RefCell::new(Timer::default());
+    /// let clock = || Duration::ZERO;
+    ///
+    /// let period = Duration::from_millis(1);
+    /// let _ticks = timer.interval(period, period, Schedule::FixedRate);
+    /// timer.borrow_mut().add(period, |_| {});
+    ///
+    /// let (now, budget) = (Duration::from_secs(1000), Duration::ZERO);
+    /// let result = timer.borrow_mut().expire_with_budget(now, &clock, budget);
+    /// assert_eq!(result, ExpireResult::Remaining);
+    /// let result = timer.borrow_mut().expire_with_budget(now, &clock, budget);
+    /// assert_eq!(result, ExpireResult::Done);
+    /// ```
+    pub fn expire_with_budget<C: Clock<T>>(
+        &mut self,
+        now: T,
+        clock: &C,
+        budget: T,
+    ) -> ExpireResult {
+        let start = clock.now();
+        while let Some(expired) = self.pop_expired(now) {
+            self.fire(expired, now);
+            if budget_spent(clock, start, budget) {
+                return ExpireResult::after(self, now);
+            }
+        }
+        ExpireResult::Done
+    }
+}
+
+impl<T: Deadline + Send + Sync + 'static, Q: TimerQueue<T>, F: TimerCallback<T>> Timer<T, Q, F> {
+    /// Add a periodic timer.
+    ///
+    /// The `callback` will be called on timer expired after `first_deadline`,
+    /// and then once per `interval` according to the `schedule`,
+    /// until the timer is cancelled.
+    ///
+    /// Besides the current time, the `callback` receives an overrun count:
+    /// the number of whole periods which have elapsed between the deadline and `now`.
+    /// It is zero unless the timer is expired late.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use alloc::sync::Arc;
+    /// use core::time::Duration;
+    /// use core::sync::atomic::{AtomicU32, Ordering};
+    /// use naive_timer::{Schedule, Timer};
+    /// extern crate alloc;
+    ///
+    /// let mut timer = Timer::default();
+    /// let count = Arc::new(AtomicU32::new(0));
+    ///
+    /// let handle = timer.add_periodic(
+    ///     Duration::from_secs(1),
+    ///     Duration::from_secs(1),
+    ///     Schedule::FixedRate,
+    ///     {
+    ///         let count = count.clone();
+    ///         move |_now, _overrun| {
+    ///             count.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///     },
+    /// );
+    ///
+    /// timer.expire(Duration::from_millis(2500));
+    /// assert_eq!(count.load(Ordering::SeqCst), 2);
+    /// assert_eq!(timer.next(), Some(Duration::from_secs(3)));
+    ///
+    /// timer.cancel(handle);
+    /// assert_eq!(timer.next(), None);
+    /// ```
+    pub fn add_periodic(
+        &mut self,
+        first_deadline: T,
+        interval: T,
+        schedule: Schedule,
+        callback: impl FnMut(T, u64) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        let action = Action::periodic(interval, schedule, callback);
+        self.insert(first_deadline, action)
+    }
+
+    /// Add a periodic timer which counts its ticks for [`Timer::poll_ticker`].
+    pub(crate) fn add_ticker(
+        &mut self,
+        first_deadline: T,
+        interval: T,
+        schedule: Schedule,
+    ) -> TimerHandle {
+        // all missed ticks are counted at once, so a fixed rate ticker
+        // goes on from the first deadline after now, as an aligned one does
+        let next = match schedule {
+            Schedule::FixedRate => next_deadline(interval, Schedule::Aligned),
+            _ => next_deadline(interval, schedule),
+        };
+        let action = Action::Tick {
+            next: Box::new(move |deadline, now| {
+                let (next, overrun) = next(deadline, now);
+                match schedule {
+                    Schedule::FixedRate => (next, overrun.saturating_add(1)),
+                    _ => (next, 1),
+                }
+            }),
+            waker: None,
+            ticks: 0,
+        };
+        self.insert(first_deadline, action)
+    }
+}
+
+impl<T: Ord + Copy, Q: TimerQueue<T>, F: TimerCallback<T>> TimerOps<T> for Timer<T, Q, F> {
+    fn insert(&mut self, deadline: T, action: Action<T>) -> TimerHandle {
+        self.timers.add(deadline, Entry::Action(action))
+    }
+
+    fn cancel(&mut self, handle: TimerHandle) -> bool {
+        Timer::cancel(self, handle)
+    }
+
+    fn reschedule(&mut self, handle: TimerHandle, deadline: T) -> bool {
+        Timer::reschedule(self, handle, deadline)
+    }
+}
+
+/// An expired timer returned by [`Timer::take_expired`].
+pub struct ExpiredTimer<T = Duration, F = Callback<T>> {
+    callback: Expired<T, F>,
+    now: T,
+}
+
+impl<T: Ord + Copy, F: TimerCallback<T>> ExpiredTimer<T, F> {
+    pub(crate) fn new(callback: Expired<T, F>, now: T) -> Self {
+        ExpiredTimer { callback, now }
+    }
+
+    /// Call the callback.
+    ///
+    /// The `timer` which it is taken from is accessed for the [`TimerContext`] of the callback,
+    /// and to reschedule a periodic timer, but not while calling the callback.
+    pub fn call<S: SharedTimer<T, Callback = F>>(self, timer: &S) {
+        let now = self.now;
+        match self.callback {
+            Expired::Once(callback) => callback(&mut TimerContext {
+                timer: &mut &*timer,
+                now,
+            }),
+            Expired::Callback(callback) => callback.call(now),
+            Expired::Wake(waker) => waker.wake(),
+            Expired::Tick => {}
+            Expired::Periodic {
+                handle,
+                deadline,
+                mut callback,
+            } => {
+                let next = callback(deadline, now);
+                timer.with(|timer| timer.restore_periodic(handle, callback, next));
+            }
+        }
+    }
+}
+
+/// The callback of an expired timer, taken out of the timer to be called.
+pub(crate) enum Expired<T, F> {
+    Once(ContextCallback<T>),
+    Callback(F),
+    Wake(Waker),
+    /// A tick of a ticker which no task waits for.
+    Tick,
+    Periodic {
+        handle: TimerHandle,
+        deadline: T,
+        callback: PeriodicCallback<T>,
+    },
+}
+
+/// A pending timer.
+pub(crate) enum Entry<T, F> {
+    Action(Action<T>),
+    Callback(F),
+}
+
+/// What to do when a timer is fired.
+pub(crate) enum Action<T> {
+    Once(ContextCallback<T>),
+    /// The callback is taken out while it is being called.
+    Periodic(Option<PeriodicCallback<T>>),
+    Wake(Waker),
+    /// A periodic timer which counts its ticks and wakes the task polling them.
+    Tick {
+        next: TickerCallback<T>,
+        waker: Option<Waker>,
+        ticks: u64,
+    },
+}
+
+impl<T: Copy> Action<T> {
+    pub(crate) fn once(callback: impl FnOnce(T) + Send + Sync + 'static) -> Self {
+        Action::Once(Box::new(move |ctx: &mut TimerContext<T>| {
+            callback(ctx.now())
+        }))
+    }
+
+    pub(crate) fn once_with_context(
+        callback: impl FnOnce(&mut TimerContext<T>) + Send + Sync + 'static,
+    ) -> Self {
+        Action::Once(Box::new(callback))
+    }
+}
+
+impl<T: Deadline + Send + Sync + 'static> Action<T> {
+    pub(crate) fn periodic(
+        interval: T,
+        schedule: Schedule,
+        mut callback: impl FnMut(T, u64) + Send + Sync + 'static,
+    ) -> Self {
+        let next = next_deadline(interval, schedule);
+        Action::Periodic(Some(Box::new(move |deadline, now| {
+            let (next, overrun) = next(deadline, now);
+            callback(now, overrun);
+            next
+        })))
+    }
+}
+
+/// Returns a function computing the next deadline of a periodic timer
+/// from its deadline and the current time, along with the overrun count.
+fn next_deadline<T: Deadline>(interval: T, schedule: Schedule) -> impl Fn(T, T) -> (T, u64) {
+    assert!(interval > T::ZERO, "interval must be non-zero");
+    move |deadline, now| {
+        let (overrun, rem) = (now - deadline).div_rem(interval);
+        let next = match schedule {
+            Schedule::FixedRate => deadline + interval,
+            Schedule::Aligned => now - rem + interval,
+            Schedule::FixedDelay => now + interval,
+        };
+        (next, overrun)
+    }
+}
+
+/// Whether `budget` has passed on `clock` since `start`.
+pub(crate) fn budget_spent<T: Deadline>(clock: &impl Clock<T>, start: T, budget: T) -> bool {
+    clock.now().max(start) - start >= budget
+}
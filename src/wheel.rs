@@ -0,0 +1,273 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{Deadline, TimerHeap, TimerQueue};
+
+/// The number of bits of a tick consumed by each level.
+const BITS: u32 = 6;
+/// The number of buckets in each level.
+const SLOTS: usize = 1 << BITS;
+
+/// A [`TimerQueue`] backed by a hashed hierarchical timing wheel.
+///
+/// Deadlines are hashed into buckets by `tick`s, which can be either
+/// a `Duration` or a number of ticks of the deadline type `T`. Each level has 64 buckets,
+/// and a bucket at level `k` covers `64^k` ticks. Timers beyond the last level
+/// are kept in an overflow list. When the wheel turns, a bucket is cascaded
+/// into lower levels, until timers in the current tick are moved into a small heap.
+///
+/// Adding and removing timers costs O(1).
+/// Expiring timers costs O(1) amortized, plus O(log n) among the timers due in the same tick.
+/// The earliest timer of each bucket is cached, so finding the next timer costs O(1),
+/// except that removing the earliest timer of a bucket scans the rest of the bucket.
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use naive_timer::{Timer, TimingWheel};
+///
+/// // 1ms per tick, 64^3 ticks (about 4.4 minutes) before overflow
+/// let mut timer = Timer::with_queue(TimingWheel::new(Duration::from_millis(1), 3));
+///
+/// timer.add(Duration::from_secs(1), |_| {});
+/// timer.expire(Duration::from_millis(999));
+/// assert_eq!(timer.next(), Some(Duration::from_secs(1)));
+/// timer.expire(Duration::from_secs(1));
+/// assert_eq!(timer.next(), None);
+/// ```
+pub struct TimingWheel<T = Duration> {
+    /// The granularity of ticks.
+    tick: T,
+    /// Bitmaps of non-empty buckets in each level.
+    occupied: Vec<u64>,
+    /// Buckets of all levels, followed by the overflow list.
+    buckets: Vec<Bucket>,
+    /// Timers whose tick is not after `cursor`.
+    ready: TimerHeap<T>,
+    /// The state of each key.
+    entries: Vec<Entry<T>>,
+    /// The tick the wheel has turned to.
+    cursor: u64,
+    /// The sequence number of the next pushed or updated key.
+    seq: u64,
+}
+
+/// Keys in a bucket, in no particular order.
+#[derive(Default)]
+struct Bucket {
+    keys: Vec<usize>,
+    /// The key with the earliest deadline and sequence number.
+    first: Option<usize>,
+}
+
+#[derive(Clone, Copy)]
+struct Entry<T> {
+    deadline: T,
+    seq: u64,
+    loc: Loc,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Loc {
+    None,
+    Ready,
+    Bucket { bucket: usize, pos: usize },
+}
+
+impl Default for TimingWheel {
+    /// A timing wheel with 1ms ticks and 4 levels.
+    fn default() -> Self {
+        TimingWheel::new(Duration::from_millis(1), 4)
+    }
+}
+
+impl<T: Deadline> TimingWheel<T> {
+    /// Create a timing wheel with the granularity of `tick` and the number of `levels`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tick` is zero, or `levels` is not in `1..=10`.
+    pub fn new(tick: T, levels: usize) -> Self {
+        assert!(tick > T::ZERO, "tick must be non-zero");
+        assert!((1..=10).contains(&levels), "levels must be in 1..=10");
+        TimingWheel {
+            tick,
+            occupied: alloc::vec![0; levels],
+            buckets: (0..levels * SLOTS + 1).map(|_| Bucket::default()).collect(),
+            ready: TimerHeap::default(),
+            entries: Vec::new(),
+            cursor: 0,
+            seq: 0,
+        }
+    }
+
+    fn levels(&self) -> usize {
+        self.occupied.len()
+    }
+
+    fn overflow(&self) -> usize {
+        self.levels() * SLOTS
+    }
+
+    fn tick_of(&self, time: T) -> u64 {
+        time.div_rem(self.tick).0
+    }
+
+    /// Put a `key` into the ready heap or a bucket, relative to `cursor`.
+    fn place(&mut self, key: usize) {
+        let Entry { deadline, seq, .. } = self.entries[key];
+        let tick = self.tick_of(deadline);
+        if tick <= self.cursor {
+            self.ready.insert(key, deadline, seq);
+            self.entries[key].loc = Loc::Ready;
+            return;
+        }
+        let level = ((63 - (tick ^ self.cursor).leading_zeros()) / BITS) as usize;
+        let bucket = if level < self.levels() {
+            self.occupied[level] |= 1 << slot_of(tick, level);
+            level * SLOTS + slot_of(tick, level)
+        } else {
+            self.overflow()
+        };
+        let pos = self.buckets[bucket].keys.len();
+        self.buckets[bucket].keys.push(key);
+        self.entries[key].loc = Loc::Bucket { bucket, pos };
+        match self.buckets[bucket].first {
+            Some(first) if self.order(first) < (deadline, seq) => {}
+            _ => self.buckets[bucket].first = Some(key),
+        }
+    }
+
+    /// Get the lowest non-empty bucket and the tick it starts from.
+    fn next_bucket(&self) -> Option<(usize, u64)> {
+        let level = self.occupied.iter().position(|&bits| bits != 0)?;
+        let slot = self.occupied[level].trailing_zeros() as usize;
+        let shift = BITS * level as u32;
+        let start = (self.cursor >> shift >> BITS << BITS | slot as u64) << shift;
+        Some((level * SLOTS + slot, start))
+    }
+
+    /// Move all keys out of a `bucket`, and place them again relative to `cursor`.
+    fn cascade(&mut self, bucket: usize) {
+        if bucket < self.overflow() {
+            self.occupied[bucket / SLOTS] &= !(1 << (bucket % SLOTS));
+        }
+        let keys = core::mem::take(&mut self.buckets[bucket]).keys;
+        for key in keys {
+            self.place(key);
+        }
+    }
+
+    /// Get the earliest key in a `bucket`.
+    fn min_in(&self, bucket: usize) -> Option<(T, usize)> {
+        let key = self.buckets[bucket].first?;
+        Some((self.entries[key].deadline, key))
+    }
+
+    /// Get the order of a `key` among keys in the same bucket.
+    fn order(&self, key: usize) -> (T, u64) {
+        (self.entries[key].deadline, self.entries[key].seq)
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.seq += 1;
+        self.seq
+    }
+}
+
+impl<T: Deadline> TimerQueue<T> for TimingWheel<T> {
+    fn push(&mut self, key: usize, deadline: T) {
+        if key >= self.entries.len() {
+            let none = Entry {
+                deadline: T::ZERO,
+                seq: 0,
+                loc: Loc::None,
+            };
+            self.entries.resize(key + 1, none);
+        }
+        debug_assert!(self.entries[key].loc == Loc::None);
+        let seq = self.next_seq();
+        self.entries[key] = Entry {
+            deadline,
+            seq,
+            loc: Loc::None,
+        };
+        self.place(key);
+    }
+
+    fn peek(&self) -> Option<(T, usize)> {
+        if let Some(first) = self.ready.peek() {
+            return Some(first);
+        }
+        match self.next_bucket() {
+            Some((bucket, _)) => self.min_in(bucket),
+            None => self.min_in(self.overflow()),
+        }
+    }
+
+    fn pop(&mut self, now: T) -> Option<(T, usize)> {
+        let target = self.tick_of(now);
+        while self.ready.is_empty() {
+            // turn the wheel to the next bucket, but no further than `now`
+            let (bucket, start) = match self.next_bucket() {
+                Some(next) => next,
+                None => match self.min_in(self.overflow()) {
+                    Some((deadline, _)) => (self.overflow(), self.tick_of(deadline)),
+                    None => {
+                        self.cursor = self.cursor.max(target);
+                        return None;
+                    }
+                },
+            };
+            if start > target {
+                let cursor = self.cursor.max(target.min(start - 1));
+                let top = BITS * self.levels() as u32;
+                let wrapped = (cursor ^ self.cursor) >> top != 0;
+                self.cursor = cursor;
+                if wrapped {
+                    self.cascade(self.overflow());
+                }
+                return None;
+            }
+            self.cursor = self.cursor.max(start);
+            self.cascade(bucket);
+        }
+        let (deadline, key) = self.ready.pop(now)?;
+        self.entries[key].loc = Loc::None;
+        Some((deadline, key))
+    }
+
+    fn remove(&mut self, key: usize) -> Option<T> {
+        let entry = self.entries.get_mut(key)?;
+        let loc = core::mem::replace(&mut entry.loc, Loc::None);
+        match loc {
+            Loc::None => return None,
+            Loc::Ready => {
+                self.ready.remove(key);
+            }
+            Loc::Bucket { bucket, pos } => {
+                let keys = &mut self.buckets[bucket].keys;
+                keys.swap_remove(pos);
+                if let Some(&moved) = keys.get(pos) {
+                    self.entries[moved].loc = Loc::Bucket { bucket, pos };
+                } else if keys.is_empty() && bucket < self.overflow() {
+                    self.occupied[bucket / SLOTS] &= !(1 << (bucket % SLOTS));
+                }
+                if self.buckets[bucket].first == Some(key) {
+                    let first = self.buckets[bucket]
+                        .keys
+                        .iter()
+                        .copied()
+                        .min_by_key(|&key| self.order(key));
+                    self.buckets[bucket].first = first;
+                }
+            }
+        }
+        Some(self.entries[key].deadline)
+    }
+}
+
+/// Get the slot of a `tick` at `level`.
+fn slot_of(tick: u64, level: usize) -> usize {
+    (tick >> (BITS * level as u32)) as usize % SLOTS
+}
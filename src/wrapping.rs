@@ -0,0 +1,146 @@
+#[cfg(feature = "alloc")]
+use crate::{Timer, TimerHandle, TimerHeap, TimerQueue};
+
+/// Extends readings of a wrapping 32-bit counter to monotonic 64-bit ticks.
+///
+/// A reading of the counter is taken as the first 64-bit tick not before the last reading,
+/// so the counter must be read at least once every `2^32` ticks.
+/// A deadline is compared with serial number arithmetic:
+/// it is taken as the nearest 64-bit tick to the last reading,
+/// within `2^31` ticks before or after it.
+///
+/// # Example
+/// ```
+/// use naive_timer::TickExtender;
+///
+/// let mut ticks = TickExtender::new();
+/// assert_eq!(ticks.extend(0xffff_fff0), 0xffff_fff0);
+/// // the counter wraps
+/// assert_eq!(ticks.extend(0x10), 0x1_0000_0010);
+/// // a deadline slightly before the wrap is in the past
+/// assert_eq!(ticks.deadline(0xffff_ff00), 0xffff_ff00);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TickExtender {
+    last: u64,
+}
+
+impl TickExtender {
+    /// Create an extender whose last reading is zero.
+    pub const fn new() -> Self {
+        TickExtender { last: 0 }
+    }
+
+    /// Extend a reading of the counter, and record it as the last reading.
+    pub fn extend(&mut self, now: u32) -> u64 {
+        self.last += now.wrapping_sub(self.last as u32) as u64;
+        self.last
+    }
+
+    /// Extend a 32-bit `deadline`, relative to the last reading.
+    pub fn deadline(&self, deadline: u32) -> u64 {
+        let offset = deadline.wrapping_sub(self.last as u32) as i32;
+        self.last.saturating_add_signed(offset as i64)
+    }
+
+    /// The last reading in 64-bit ticks.
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+}
+
+/// A timer driven by a wrapping 32-bit tick counter.
+///
+/// Deadlines and the current time are raw 32-bit counter values.
+/// They are extended to 64-bit ticks by a [`TickExtender`],
+/// so the timer keeps working when the counter wraps,
+/// as long as [`expire`](WrappingTimer::expire) is called at least once every `2^32` ticks
+/// and deadlines are within `2^31` ticks from the last `now`.
+///
+/// # Example
+/// ```
+/// use naive_timer::WrappingTimer;
+///
+/// let mut timer = WrappingTimer::default();
+/// timer.expire(0xffff_ff00);
+///
+/// // the deadline is after the counter wraps
+/// timer.add(0x100, |now| assert_eq!(now, 0x100));
+///
+/// timer.expire(0xffff_fff0);
+/// assert_eq!(timer.next(), Some(0x100));
+///
+/// timer.expire(0x100);
+/// assert_eq!(timer.next(), None);
+/// ```
+#[cfg(feature = "alloc")]
+pub struct WrappingTimer<Q = TimerHeap<u64>> {
+    timer: Timer<u64, Q>,
+    ticks: TickExtender,
+}
+
+#[cfg(feature = "alloc")]
+impl Default for WrappingTimer {
+    fn default() -> Self {
+        WrappingTimer::with_queue(TimerHeap::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Q: TimerQueue<u64>> WrappingTimer<Q> {
+    /// Create a timer which stores pending timers in `queue`.
+    pub fn with_queue(queue: Q) -> Self {
+        WrappingTimer {
+            timer: Timer::with_queue(queue),
+            ticks: TickExtender::new(),
+        }
+    }
+
+    /// Add a timer.
+    ///
+    /// The `callback` will be called on timer expired after `deadline`.
+    pub fn add(
+        &mut self,
+        deadline: u32,
+        callback: impl FnOnce(u32) + Send + Sync + 'static,
+    ) -> TimerHandle {
+        let deadline = self.ticks.deadline(deadline);
+        self.timer.add(deadline, move |now| callback(now as u32))
+    }
+
+    /// Cancel a timer. See [`Timer::cancel`].
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.timer.cancel(handle)
+    }
+
+    /// Reschedule a timer to a new `deadline`. See [`Timer::reschedule`].
+    pub fn reschedule(&mut self, handle: TimerHandle, deadline: u32) -> bool {
+        let deadline = self.ticks.deadline(deadline);
+        self.timer.reschedule(handle, deadline)
+    }
+
+    /// Expire timers.
+    ///
+    /// Given the current counter value `now`, trigger and remove all expired timers.
+    pub fn expire(&mut self, now: u32) {
+        let now = self.ticks.extend(now);
+        self.timer.expire(now);
+    }
+
+    /// Get next timer, as a counter value.
+    pub fn next(&self) -> Option<u32> {
+        self.timer.next().map(|deadline| deadline as u32)
+    }
+
+    /// Get the underlying timer in 64-bit ticks.
+    ///
+    /// Use [`ticks`](WrappingTimer::ticks) to extend counter values for it.
+    pub fn timer(&mut self) -> &mut Timer<u64, Q> {
+        &mut self.timer
+    }
+
+    /// Get the extender of counter values.
+    pub fn ticks(&self) -> &TickExtender {
+        &self.ticks
+    }
+}
@@ -0,0 +1,219 @@
+//! Randomized tests of `TimingWheel` against a model of pending timers.
+
+#![cfg(feature = "alloc")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use naive_timer::{Schedule, Timer, TimerHandle, TimingWheel};
+
+/// A xorshift generator, so that failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// A pending timer of the model.
+#[derive(Clone, Copy)]
+struct Pending {
+    deadline: u64,
+    id: u64,
+    handle: TimerHandle,
+}
+
+/// Wheel configurations: the tick, the number of levels, and the start time.
+///
+/// Small wheels put most timers into the overflow list, and a start time far from zero
+/// makes the cursor wrap around the top level.
+fn wheels() -> Vec<(Duration, usize, u64)> {
+    vec![
+        (Duration::from_millis(1), 4, 0),
+        (Duration::from_millis(1), 1, 0),
+        (Duration::from_millis(1), 2, 0),
+        (Duration::from_millis(3), 2, 0),
+        (Duration::from_micros(300), 1, 0),
+        (Duration::from_millis(7), 1, 0),
+        (Duration::from_millis(1), 4, 1_700_000_000_000),
+    ]
+}
+
+/// Add, cancel and expire timers at random, with deadlines up to `range` ms ahead
+/// and the time advancing up to `step` ms per expire.
+fn random_ops(wheel: TimingWheel, start: u64, range: u64, step: u64, seed: u64) {
+    let mut timer = Timer::with_queue(wheel);
+    let mut rng = Rng(seed);
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut model: Vec<Pending> = Vec::new();
+    let mut now = start;
+    for id in 0..10_000 {
+        match rng.below(4) {
+            0 | 1 => {
+                let deadline = now + rng.below(range);
+                let fired = fired.clone();
+                let handle = timer.add(Duration::from_millis(deadline), move |_| {
+                    fired.lock().unwrap().push(id)
+                });
+                model.push(Pending {
+                    deadline,
+                    id,
+                    handle,
+                });
+            }
+            2 if !model.is_empty() => {
+                let i = rng.below(model.len() as u64) as usize;
+                let handle = model.remove(i).handle;
+                assert!(timer.cancel(handle));
+                assert!(!timer.cancel(handle));
+            }
+            _ => {
+                now += rng.below(step);
+                timer.expire(Duration::from_millis(now));
+                check_expired(&mut model, &fired, now);
+                for pending in &model {
+                    assert!(pending.deadline > now);
+                }
+                let next = model.iter().map(|p| p.deadline).min();
+                assert_eq!(timer.next(), next.map(Duration::from_millis));
+            }
+        }
+    }
+}
+
+/// Reschedule timers at random.
+fn random_reschedule(wheel: TimingWheel, seed: u64) {
+    let mut timer = Timer::with_queue(wheel);
+    let mut rng = Rng(seed);
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let mut model: Vec<Pending> = Vec::new();
+    let mut now = 0;
+    for id in 0..10_000 {
+        match rng.below(4) {
+            0 => {
+                let deadline = now + rng.below(100);
+                let fired = fired.clone();
+                let handle = timer.add(Duration::from_millis(deadline), move |_| {
+                    fired.lock().unwrap().push(id)
+                });
+                model.push(Pending {
+                    deadline,
+                    id,
+                    handle,
+                });
+            }
+            1 | 2 if !model.is_empty() => {
+                // a rescheduled timer is ordered after others with the same deadline
+                let i = rng.below(model.len() as u64) as usize;
+                let mut pending = model.remove(i);
+                pending.deadline = now + rng.below(100);
+                assert!(timer.reschedule(pending.handle, Duration::from_millis(pending.deadline)));
+                model.push(pending);
+            }
+            _ => {
+                now += rng.below(20);
+                timer.expire(Duration::from_millis(now));
+                let expired = check_expired(&mut model, &fired, now);
+                for pending in expired {
+                    assert!(!timer.reschedule(pending.handle, Duration::ZERO));
+                }
+                let next = model.iter().map(|p| p.deadline).min();
+                assert_eq!(timer.next(), next.map(Duration::from_millis));
+            }
+        }
+    }
+}
+
+/// Check that the timers due at `now` are fired in the order of their deadlines,
+/// and then in the order they were added to the model. Returns the fired timers.
+fn check_expired(model: &mut Vec<Pending>, fired: &Mutex<Vec<u64>>, now: u64) -> Vec<Pending> {
+    // a stable sort keeps the order of the model among the same deadline
+    let mut expired: Vec<Pending> = model
+        .iter()
+        .filter(|p| p.deadline <= now)
+        .copied()
+        .collect();
+    expired.sort_by_key(|p| p.deadline);
+    model.retain(|p| p.deadline > now);
+    let ids: Vec<u64> = expired.iter().map(|p| p.id).collect();
+    assert_eq!(*fired.lock().unwrap(), ids);
+    fired.lock().unwrap().clear();
+    expired
+}
+
+#[test]
+fn add_cancel_expire() {
+    for (i, (tick, levels, start)) in wheels().into_iter().enumerate() {
+        let seed = 0x1234567 + i as u64;
+        random_ops(TimingWheel::new(tick, levels), start, 100, 20, seed);
+        random_ops(TimingWheel::new(tick, levels), start, 100_000, 2_000, seed);
+        random_ops(TimingWheel::new(tick, levels), start, 1_000_000, 2, seed);
+    }
+}
+
+#[test]
+fn far_deadlines() {
+    // most timers are in the overflow list, and the cursor wraps the top level often
+    random_ops(
+        TimingWheel::new(Duration::from_millis(3), 2),
+        0,
+        1_000_000,
+        200_000,
+        0x42,
+    );
+    random_ops(
+        TimingWheel::default(),
+        1_700_000_000_000,
+        100_000_000,
+        20_000,
+        0x43,
+    );
+}
+
+#[test]
+fn reschedule() {
+    for (i, (tick, levels, _)) in wheels().into_iter().enumerate() {
+        random_reschedule(TimingWheel::new(tick, levels), 0x98765 + i as u64);
+    }
+}
+
+#[test]
+fn same_tick_in_order() {
+    let mut timer = Timer::with_queue(TimingWheel::new(Duration::from_millis(7), 1));
+    let mut rng = Rng(0x5555);
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    for id in 0..5000 {
+        let deadline = rng.below(10);
+        let fired = fired.clone();
+        timer.add(Duration::from_millis(deadline), move |_| {
+            fired.lock().unwrap().push((deadline, id))
+        });
+    }
+    timer.expire(Duration::from_secs(1));
+    let fired = fired.lock().unwrap();
+    assert_eq!(fired.len(), 5000);
+    assert!(fired.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn u64_ticks() {
+    let mut timer = Timer::<u64, _>::with_queue(TimingWheel::new(10, 2));
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    timer.add_periodic(100, 50, Schedule::Aligned, {
+        let fired = fired.clone();
+        move |now, overrun| fired.lock().unwrap().push((now, overrun))
+    });
+    timer.expire(99);
+    timer.expire(100);
+    timer.expire(260);
+    assert_eq!(*fired.lock().unwrap(), [(100, 0), (260, 2)]);
+    assert_eq!(timer.next(), Some(300));
+}